@@ -0,0 +1,234 @@
+// ChaCha20-Poly1305 (RFC 8439) の実装。ノートの暗号化に使う
+
+const TAG_LEN: usize = 16;
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+// ChaCha20 の1ブロック (64バイト) の鍵ストリーム
+fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut initial = [0u32; 16];
+    initial[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+    for (word, chunk) in initial[4..12].iter_mut().zip(key.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    initial[12] = counter;
+    for (word, chunk) in initial[13..].iter_mut().zip(nonce.chunks(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let mut state = initial;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; 64];
+    for (chunk, (word, init)) in out.chunks_mut(4).zip(state.iter().zip(initial)) {
+        chunk.copy_from_slice(&word.wrapping_add(init).to_le_bytes());
+    }
+    out
+}
+
+// カウンタ 1 から始まる鍵ストリームとの排他的論理和
+fn chacha20_xor(key: &[u8; 32], nonce: &[u8; 12], data: &mut [u8]) {
+    for (index, chunk) in data.chunks_mut(64).enumerate() {
+        let keystream = chacha20_block(key, 1 + index as u32, nonce);
+        for (byte, k) in chunk.iter_mut().zip(keystream) {
+            *byte ^= k;
+        }
+    }
+}
+
+// Poly1305 のメッセージ認証コード。26ビットずつ5つのリムで 2^130 - 5 を法として計算する
+fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG_LEN] {
+    const MASK: u64 = 0x3ff_ffff;
+    let le32 =
+        |bytes: &[u8], i: usize| u64::from(u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap()));
+
+    let r = [
+        le32(key, 0) & 0x3ff_ffff,
+        (le32(key, 3) >> 2) & 0x3ff_ff03,
+        (le32(key, 6) >> 4) & 0x3ff_c0ff,
+        (le32(key, 9) >> 6) & 0x3f0_3fff,
+        (le32(key, 12) >> 8) & 0x00f_ffff,
+    ];
+    let s = [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5];
+    let mut h = [0u64; 5];
+
+    for chunk in message.chunks(16) {
+        let mut block = [0u8; 17];
+        block[..chunk.len()].copy_from_slice(chunk);
+        block[chunk.len()] = 1;
+        let high = u64::from(block[16]) << 24;
+
+        h[0] += le32(&block, 0) & MASK;
+        h[1] += (le32(&block, 3) >> 2) & MASK;
+        h[2] += (le32(&block, 6) >> 4) & MASK;
+        h[3] += (le32(&block, 9) >> 6) & MASK;
+        h[4] += (le32(&block, 12) >> 8) | high;
+
+        let d = [
+            h[0] * r[0] + h[1] * s[3] + h[2] * s[2] + h[3] * s[1] + h[4] * s[0],
+            h[0] * r[1] + h[1] * r[0] + h[2] * s[3] + h[3] * s[2] + h[4] * s[1],
+            h[0] * r[2] + h[1] * r[1] + h[2] * r[0] + h[3] * s[3] + h[4] * s[2],
+            h[0] * r[3] + h[1] * r[2] + h[2] * r[1] + h[3] * r[0] + h[4] * s[3],
+            h[0] * r[4] + h[1] * r[3] + h[2] * r[2] + h[3] * r[1] + h[4] * r[0],
+        ];
+        let mut carry = 0;
+        for i in 0..5 {
+            let value = d[i] + carry;
+            h[i] = value & MASK;
+            carry = value >> 26;
+        }
+        h[0] += carry * 5;
+        h[1] += h[0] >> 26;
+        h[0] &= MASK;
+    }
+
+    // 完全に繰り上げてから、h >= p であれば p を引く
+    let mut carry = 0;
+    for limb in h.iter_mut() {
+        *limb += carry;
+        carry = *limb >> 26;
+        *limb &= MASK;
+    }
+    h[0] += carry * 5;
+    h[1] += h[0] >> 26;
+    h[0] &= MASK;
+
+    let mut g = [0u64; 5];
+    let mut carry = 5;
+    for (g, h) in g.iter_mut().zip(h) {
+        *g = h + carry;
+        carry = *g >> 26;
+        *g &= MASK;
+    }
+    if carry != 0 {
+        h = g;
+    }
+
+    let acc = u128::from(h[0])
+        | u128::from(h[1]) << 26
+        | u128::from(h[2]) << 52
+        | u128::from(h[3]) << 78
+        | u128::from(h[4]) << 104;
+    let s = u128::from_le_bytes(key[16..].try_into().unwrap());
+    acc.wrapping_add(s).to_le_bytes()
+}
+
+// 追加データと暗号文から Poly1305 のタグを計算
+fn compute_tag(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+    let poly_key: [u8; 32] = chacha20_block(key, 0, nonce)[..32].try_into().unwrap();
+    let pad = |len: usize| vec![0u8; (16 - len % 16) % 16];
+
+    let mut mac_data = Vec::new();
+    mac_data.extend_from_slice(aad);
+    mac_data.extend(pad(aad.len()));
+    mac_data.extend_from_slice(ciphertext);
+    mac_data.extend(pad(ciphertext.len()));
+    mac_data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    mac_data.extend_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+    poly1305(&poly_key, &mac_data)
+}
+
+// 平文を暗号化し、末尾に認証タグを付けた暗号文を返す
+pub fn seal(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut ciphertext = plaintext.to_vec();
+    chacha20_xor(key, nonce, &mut ciphertext);
+    let tag = compute_tag(key, nonce, aad, &ciphertext);
+    ciphertext.extend_from_slice(&tag);
+    ciphertext
+}
+
+// 認証タグを確認してから復号する。改ざんされていれば None
+pub fn open(key: &[u8; 32], nonce: &[u8; 12], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+    let split = sealed.len().checked_sub(TAG_LEN)?;
+    let (ciphertext, tag) = sealed.split_at(split);
+    let expected = compute_tag(key, nonce, aad, ciphertext);
+    let difference = expected
+        .iter()
+        .zip(tag)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if difference != 0 {
+        return None;
+    }
+
+    let mut plaintext = ciphertext.to_vec();
+    chacha20_xor(key, nonce, &mut plaintext);
+    Some(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        hex.as_bytes()
+            .chunks(2)
+            .map(|chunk| u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    // RFC 8439 2.8.2 の ChaCha20-Poly1305 のテストベクタ
+    #[test]
+    fn chacha20_poly1305_matches_rfc_8439() {
+        let key: [u8; 32] =
+            unhex("808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f")
+                .try_into()
+                .unwrap();
+        let nonce: [u8; 12] = unhex("070000004041424344454647").try_into().unwrap();
+        let aad = unhex("50515253c0c1c2c3c4c5c6c7");
+        let plaintext: &[u8] = b"Ladies and Gentlemen of the class of '99: If I could offer you \
+            only one tip for the future, sunscreen would be it.";
+        let ciphertext = "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+                          3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+                          92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+                          3ff4def08e4b7a9de576d26586cec64b6116";
+        let tag = "1ae10b594f09e26a7e902ecbd0600691";
+
+        let sealed = seal(&key, &nonce, &aad, plaintext);
+        assert_eq!(hex(&sealed), format!("{}{}", ciphertext, tag));
+        assert_eq!(
+            open(&key, &nonce, &aad, &sealed).as_deref(),
+            Some(plaintext)
+        );
+    }
+
+    #[test]
+    fn chacha20_poly1305_rejects_tampering() {
+        let key = [0x42; 32];
+        let nonce = [0x24; 12];
+        let sealed = seal(&key, &nonce, b"header", b"note plaintext");
+
+        // タグ、暗号文、追加データ、ノンスのどれが変わっても復号できない
+        let mut tampered_tag = sealed.clone();
+        *tampered_tag.last_mut().unwrap() ^= 1;
+        assert_eq!(open(&key, &nonce, b"header", &tampered_tag), None);
+        let mut tampered_ciphertext = sealed.clone();
+        tampered_ciphertext[0] ^= 1;
+        assert_eq!(open(&key, &nonce, b"header", &tampered_ciphertext), None);
+        assert_eq!(open(&key, &nonce, b"footer", &sealed), None);
+        assert_eq!(open(&key, &[0x25; 12], b"header", &sealed), None);
+        // 短すぎる入力も受け付けない
+        assert_eq!(open(&key, &nonce, b"header", &sealed[..15]), None);
+    }
+}
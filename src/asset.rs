@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::group::Point;
+use crate::hash::hash32;
+
+// 資産タイプの識別子。資産のメタデータ（名前、小数点以下の桁数、エポック）から決定的に導出される
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetType([u8; 32]);
+
+impl AssetType {
+    pub fn new(name: &str, decimals: u8, epoch: Option<u64>) -> Self {
+        let epoch_bytes = match epoch {
+            Some(epoch) => [&[1u8][..], &epoch.to_le_bytes()].concat(),
+            None => vec![0],
+        };
+        AssetType(hash32(
+            b"MASPsim_AssetId",
+            &[
+                &(name.len() as u64).to_le_bytes(),
+                name.as_bytes(),
+                &[decimals],
+                &epoch_bytes,
+            ],
+        ))
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        AssetType(bytes)
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl fmt::Debug for AssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AssetType(")?;
+        for byte in &self.0[..8] {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, "..)")
+    }
+}
+
+// 資産のメタデータ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetInfo {
+    pub name: String,
+    pub decimals: u8,
+    pub epoch: Option<u64>,
+}
+
+impl AssetInfo {
+    pub fn asset_type(&self) -> AssetType {
+        AssetType::new(&self.name, self.decimals, self.epoch)
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.epoch {
+            Some(epoch) => write!(f, "{}@{}", self.name, epoch),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+// 登録済みの資産の一覧。識別子からメタデータを引いて表示に使い、発行者の公開鍵を保持する
+#[derive(Debug, Clone, Default)]
+pub struct AssetRegistry {
+    assets: HashMap<AssetType, AssetInfo>,
+    issuers: HashMap<AssetType, Point>,
+}
+
+impl AssetRegistry {
+    pub fn new() -> Self {
+        AssetRegistry::default()
+    }
+
+    // 資産を登録し、その識別子を返す
+    pub fn register(&mut self, name: &str, decimals: u8, epoch: Option<u64>) -> AssetType {
+        let info = AssetInfo {
+            name: name.to_string(),
+            decimals,
+            epoch,
+        };
+        let asset_type = info.asset_type();
+        self.assets.insert(asset_type, info);
+        asset_type
+    }
+
+    pub fn get(&self, asset_type: &AssetType) -> Option<&AssetInfo> {
+        self.assets.get(asset_type)
+    }
+
+    pub fn contains(&self, asset_type: &AssetType) -> bool {
+        self.assets.contains_key(asset_type)
+    }
+
+    // 登録済みの資産に発行者の公開鍵を紐付ける。登録されていない資産なら false
+    pub fn set_issuer(&mut self, asset_type: &AssetType, issuer: Point) -> bool {
+        if !self.contains(asset_type) {
+            return false;
+        }
+        self.issuers.insert(*asset_type, issuer);
+        true
+    }
+
+    pub fn issuer(&self, asset_type: &AssetType) -> Option<&Point> {
+        self.issuers.get(asset_type)
+    }
+
+    pub fn assets(&self) -> impl Iterator<Item = (&AssetType, &AssetInfo)> {
+        self.assets.iter()
+    }
+
+    pub fn issuers(&self) -> impl Iterator<Item = (&AssetType, &Point)> {
+        self.issuers.iter()
+    }
+
+    // メタデータから識別子を導出し、登録済みならそれを返す。
+    // 名前とエポックが同じでも桁数が違えば別の資産なので、桁数も指定する
+    pub fn lookup(&self, name: &str, decimals: u8, epoch: Option<u64>) -> Option<AssetType> {
+        let asset_type = AssetType::new(name, decimals, epoch);
+        self.contains(&asset_type).then_some(asset_type)
+    }
+
+    // 最小単位の量を、小数点以下の桁数に合わせて資産名付きで表示する
+    pub fn format_amount(&self, asset_type: &AssetType, amount: u64) -> String {
+        let Some(info) = self.get(asset_type) else {
+            return format!("{} {:?}", amount, asset_type);
+        };
+        let unit = match 10u128.checked_pow(u32::from(info.decimals)) {
+            Some(unit) if info.decimals > 0 => unit,
+            _ => return format!("{} {}", amount, info),
+        };
+        let amount = u128::from(amount);
+        format!(
+            "{}.{:0width$} {}",
+            amount / unit,
+            amount % unit,
+            info,
+            width = usize::from(info.decimals)
+        )
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use crate::encoding;
+use crate::hash::hash32;
+use crate::transaction::Transaction;
+use crate::transparent::TransparentAddress;
+use crate::tree::Anchor;
+
+// ブロックの識別子。ヘッダの正規の符号化のハッシュ
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockHash(pub [u8; 32]);
+
+impl BlockHash {
+    // 最初のブロックの親として使う値
+    pub const GENESIS_PARENT: BlockHash = BlockHash([0; 32]);
+}
+
+impl fmt::Debug for BlockHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BlockHash(")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// ブロックヘッダ。親ブロックのハッシュと高さで前のブロックにつながり、
+// ブロックを適用したあとのツリーのルートとヌリファイアの集合のダイジェストで台帳の状態に拘束される
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub prev_hash: BlockHash,
+    pub height: u64, // 最初のブロックが 1
+    pub tree_root: Anchor,
+    pub nullifier_digest: [u8; 32],
+    pub transactions_digest: [u8; 32], // 含まれるトランザクションの txid の並びのダイジェスト
+    pub fee_recipient: TransparentAddress, // ブロックを作った人。含まれるトランザクションの手数料を受け取る
+}
+
+impl BlockHeader {
+    pub fn hash(&self) -> BlockHash {
+        BlockHash(hash32(b"MASPsim_BlockHdr", &[&encoding::encode(self)]))
+    }
+}
+
+// ブロック。ヘッダと、順に適用されるトランザクションの列
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    pub fn hash(&self) -> BlockHash {
+        self.header.hash()
+    }
+}
+
+// トランザクションの並びのダイジェスト。txid を順に並べてハッシュする
+pub fn transactions_digest(transactions: &[Transaction]) -> [u8; 32] {
+    let txids: Vec<[u8; 32]> = transactions
+        .iter()
+        .map(|transaction| transaction.txid().0)
+        .collect();
+    let parts: Vec<&[u8]> = txids.iter().map(|txid| &txid[..]).collect();
+    hash32(b"MASPsim_BlockTxs", &parts)
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use crate::asset::AssetType;
+use crate::error::BuildError;
+use crate::fee::FeeRule;
+use crate::issuance::IssuanceKey;
+use crate::keys::PaymentAddress;
+use crate::note::Note;
+use crate::proof::ProofSystem;
+use crate::transaction::{Transaction, UnauthorizedTransaction};
+use crate::transparent::TransparentAddress;
+use crate::wallet::{ReceivedNote, Wallet};
+
+// 支払う人のノートから入力を選び、お釣りの出力を自動で加えてトランザクションを組み立てる。
+// 複数の支払う人と資産タイプを1つのトランザクションにまとめられる
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder<'a> {
+    sender: &'a Wallet,
+    payments: Vec<(&'a Wallet, Note)>, // 支払う人と作成する出力
+    fee: Option<u64>,                  // 送信者が支払う手数料の量。資産タイプは手数料の規則で決まる
+    fee_rule: Option<FeeRule>,         // 手数料を指定しない場合に、最低額を見積もるための規則
+    // 送信者の透明なアカウントから引き出す量 (資産タイプ, 量, ノンス)
+    transparent_inputs: Vec<(AssetType, u64, u64)>,
+    // 送信者のノートから透明なアカウントへ送る量
+    transparent_outputs: Vec<(TransparentAddress, AssetType, u64)>,
+    mints: Vec<(&'a IssuanceKey, AssetType, u64)>, // 発行者の鍵で新しく作る量
+    burns: Vec<(AssetType, u64)>,                  // 送信者のノートから破棄する量
+}
+
+impl<'a> TransactionBuilder<'a> {
+    // 送信者を指定。アンカーは送信者が同期したツリーのルート
+    pub fn new(sender: &'a Wallet) -> Self {
+        TransactionBuilder {
+            sender,
+            payments: Vec::new(),
+            fee: None,
+            fee_rule: None,
+            transparent_inputs: Vec::new(),
+            transparent_outputs: Vec::new(),
+            mints: Vec::new(),
+            burns: Vec::new(),
+        }
+    }
+
+    // 送信者から受取人のアドレスへ資産を送る出力を追加
+    pub fn add_output(self, recipient: PaymentAddress, asset_type: AssetType, amount: u64) -> Self {
+        let sender = self.sender;
+        self.add_output_from(sender, recipient, asset_type, amount)
+    }
+
+    // 送信者以外のウォレットが支払う出力を追加。資産の交換などに使う。支払う人は送信者と同じブロックまで同期している必要がある
+    pub fn add_output_from(
+        mut self,
+        payer: &'a Wallet,
+        recipient: PaymentAddress,
+        asset_type: AssetType,
+        amount: u64,
+    ) -> Self {
+        self.payments
+            .push((payer, Note::new(asset_type, amount, recipient)));
+        self
+    }
+
+    // 送信者の透明なアカウントから引き出す量を追加。nonce はアカウントの現在のノンス。
+    // 使い切らなかった分は送信者のノートになる
+    pub fn add_transparent_input(mut self, asset_type: AssetType, amount: u64, nonce: u64) -> Self {
+        self.transparent_inputs.push((asset_type, amount, nonce));
+        self
+    }
+
+    // 送信者のノートから透明なアカウントへ送る出力を追加
+    pub fn add_transparent_output(
+        mut self,
+        address: TransparentAddress,
+        asset_type: AssetType,
+        amount: u64,
+    ) -> Self {
+        self.transparent_outputs.push((address, asset_type, amount));
+        self
+    }
+
+    // 発行者の鍵で資産を新しく作る。作った量は透明な入力と同じように出力に使え、使い切らなかった分は送信者のノートになる
+    pub fn mint(mut self, issuer: &'a IssuanceKey, asset_type: AssetType, amount: u64) -> Self {
+        self.mints.push((issuer, asset_type, amount));
+        self
+    }
+
+    // 送信者のノートから資産を破棄する。同じ資産タイプを何度も指定すると量を合計し、
+    // 合計が表現できなければ build が AmountOverflow を返す
+    pub fn burn(mut self, asset_type: AssetType, amount: u64) -> Self {
+        self.burns.push((asset_type, amount));
+        self
+    }
+
+    // 手数料の量を指定。手数料は with_fee_rule の規則の資産タイプで払う
+    pub fn with_fee(mut self, amount: u64) -> Self {
+        self.fee = Some(amount);
+        self
+    }
+
+    // 手数料を規則の最低額から見積もる。with_fee で量を指定した場合はそちらを使う
+    pub fn with_fee_rule(mut self, fee_rule: FeeRule) -> Self {
+        self.fee_rule = Some(fee_rule);
+        self
+    }
+
+    // 払う手数料。指定がなければ、選ぶノートとお釣りの出力を含めた入力と出力の数から規則の最低額を見積もる。
+    // 手数料を増やすと選ぶノートが増えることがあるので、最低額が手数料を超えなくなるまで繰り返す
+    pub fn estimate_fee(&self) -> Result<Option<(AssetType, u64)>, BuildError> {
+        let Some(fee_rule) = &self.fee_rule else {
+            return match self.fee {
+                Some(_) => Err(BuildError::NoFeeRule),
+                None => Ok(None),
+            };
+        };
+        if let Some(amount) = self.fee {
+            return Ok(Some((fee_rule.asset_type, amount)));
+        }
+        let mut fee = fee_rule.minimum(self.payments.len() + self.transparent_actions());
+        loop {
+            let plan = self.plan(Some((fee_rule.asset_type, fee)))?;
+            let required = fee_rule
+                .minimum(plan.inputs.len() + plan.outputs.len() + self.transparent_actions());
+            if required <= fee {
+                return Ok(Some((fee_rule.asset_type, fee)));
+            }
+            fee = required;
+        }
+    }
+
+    fn transparent_actions(&self) -> usize {
+        self.transparent_inputs.len() + self.transparent_outputs.len()
+    }
+
+    // 支払う人と資産タイプごとに必要な量を満たすまで大きいノートから順に選び、余りをその人へのお釣りにする
+    fn plan(&self, fee: Option<(AssetType, u64)>) -> Result<Plan<'a>, BuildError> {
+        // シールドされたプールから出ていく量。手数料、透明な出力、バーンは出ていき、透明な入力とミントは入ってくる
+        let mut value_balance: BTreeMap<AssetType, i128> = BTreeMap::new();
+        for (asset_type, amount) in fee.into_iter().chain(self.burns.clone()) {
+            *value_balance.entry(asset_type).or_default() += i128::from(amount);
+        }
+        for (_, asset_type, amount) in &self.transparent_outputs {
+            *value_balance.entry(*asset_type).or_default() += i128::from(*amount);
+        }
+        for (asset_type, amount, _) in &self.transparent_inputs {
+            *value_balance.entry(*asset_type).or_default() -= i128::from(*amount);
+        }
+        for (_, asset_type, amount) in &self.mints {
+            *value_balance.entry(*asset_type).or_default() -= i128::from(*amount);
+        }
+
+        // 支払う人と資産タイプごとに、ノートでまかなう必要のある量。負の値は余った透明な入力
+        let mut payers: Vec<&'a Wallet> = vec![self.sender];
+        let mut required: BTreeMap<(usize, AssetType), i128> = BTreeMap::new();
+        for (asset_type, value) in &value_balance {
+            required.insert((0, *asset_type), *value);
+        }
+        for (payer, note) in &self.payments {
+            let index = match payers
+                .iter()
+                .position(|wallet| std::ptr::eq(*wallet, *payer))
+            {
+                Some(index) => index,
+                None if payer.anchor() != self.sender.anchor() => {
+                    return Err(BuildError::UnsyncedPayer(payer.id.clone()));
+                }
+                None => {
+                    payers.push(payer);
+                    payers.len() - 1
+                }
+            };
+            *required.entry((index, note.asset_type)).or_default() += i128::from(note.amount);
+        }
+
+        let mut inputs = Vec::new();
+        let mut outputs: Vec<Note> = self.payments.iter().map(|(_, note)| note.clone()).collect();
+        for ((index, asset_type), amount) in required {
+            let payer = payers[index];
+            let target =
+                u64::try_from(amount.max(0)).map_err(|_| BuildError::AmountOverflow(asset_type))?;
+            let (selected, total) = select_notes(payer, asset_type, target)?;
+            inputs.extend(selected.into_iter().map(|received| (payer, received)));
+            let change = u64::try_from(i128::from(total) - amount)
+                .map_err(|_| BuildError::AmountOverflow(asset_type))?;
+            if change > 0 {
+                outputs.push(Note::new(asset_type, change, payer.address()));
+            }
+        }
+        Ok(Plan {
+            payers,
+            value_balance,
+            inputs,
+            outputs,
+        })
+    }
+
+    // 手数料を決めてノートを選び、署名を集めてから証明を作成する
+    pub fn build<P: ProofSystem>(self, prover: &P) -> Result<Transaction, BuildError> {
+        let fee = self.estimate_fee()?;
+        let plan = self.plan(fee)?;
+        let inputs = plan
+            .inputs
+            .into_iter()
+            .map(|(payer, received)| payer.spend(received))
+            .collect();
+
+        let mut transaction =
+            UnauthorizedTransaction::new(self.sender.anchor(), inputs, plan.outputs);
+        for (asset_type, amount, nonce) in self.transparent_inputs {
+            transaction = transaction.add_transparent_input(
+                self.sender.transparent_public_key(),
+                asset_type,
+                amount,
+                nonce,
+            );
+        }
+        for (address, asset_type, amount) in self.transparent_outputs {
+            transaction = transaction.add_transparent_output(address, asset_type, amount);
+        }
+        for (issuer, asset_type, amount) in &self.mints {
+            transaction = transaction.add_issuance(issuer.public_key(), *asset_type, *amount);
+        }
+        for (asset_type, amount) in self.burns {
+            transaction = transaction.with_burn(asset_type, amount)?;
+        }
+        for (asset_type, value) in plan.value_balance {
+            let value = i64::try_from(value).map_err(|_| BuildError::AmountOverflow(asset_type))?;
+            transaction = transaction.with_value_balance(asset_type, value);
+        }
+        if let Some((_, amount)) = fee {
+            transaction = transaction.with_fee(amount);
+        }
+        // すべての変更を終えてから、支払う人それぞれが自分の入力に、発行者がミントに署名する
+        for payer in plan.payers {
+            payer.authorize(&mut transaction);
+        }
+        for (issuer, _, _) in self.mints {
+            transaction.sign_issuance(issuer);
+        }
+        transaction.prove(prover)
+    }
+}
+
+// 選んだノートと作成する出力。支払う人の一覧は送信者が先頭
+struct Plan<'a> {
+    payers: Vec<&'a Wallet>,
+    value_balance: BTreeMap<AssetType, i128>,
+    inputs: Vec<(&'a Wallet, &'a ReceivedNote)>,
+    outputs: Vec<Note>,
+}
+
+// ウォレットの資産タイプの使えるノートを大きい順に、合計が amount 以上になるまで選ぶ。選んだノートと合計を返す
+fn select_notes(
+    wallet: &Wallet,
+    asset_type: AssetType,
+    amount: u64,
+) -> Result<(Vec<&ReceivedNote>, u64), BuildError> {
+    let mut candidates: Vec<&ReceivedNote> = wallet
+        .spendable_notes()
+        .filter(|received| received.note.asset_type == asset_type)
+        .collect();
+    candidates.sort_by_key(|received| std::cmp::Reverse(received.note.amount));
+
+    let mut selected = Vec::new();
+    let mut total: u64 = 0;
+    for received in candidates {
+        if total >= amount {
+            break;
+        }
+        total = total
+            .checked_add(received.note.amount)
+            .ok_or(BuildError::AmountOverflow(asset_type))?;
+        selected.push(received);
+    }
+    if total < amount {
+        return Err(BuildError::InsufficientFunds {
+            asset_type,
+            required: amount,
+            available: total,
+        });
+    }
+    Ok((selected, total))
+}
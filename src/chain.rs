@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+
+use crate::block::{transactions_digest, Block, BlockHash, BlockHeader};
+use crate::error::BlockError;
+use crate::ledger::Ledger;
+use crate::proof::{MockProver, ProofSystem};
+use crate::transaction::Transaction;
+use crate::transparent::TransparentAddress;
+use crate::tree::Anchor;
+
+// アンカーとして使える直近のブロックの数の既定値
+pub const DEFAULT_ANCHOR_WINDOW: usize = 10;
+
+// ブロックの列と、それを順に適用した台帳の状態。
+// トランザクションのアンカーは、直近のブロックを適用したあとのツリーのルートでなければならない
+#[derive(Debug, Clone)]
+pub struct Chain<P: ProofSystem = MockProver> {
+    ledger: Ledger<P>,
+    blocks: Vec<Block>,
+    recent_roots: VecDeque<Anchor>, // 直近のブロックのあとのツリーのルート。古いものから順
+    anchor_window: usize,
+}
+
+impl<P: ProofSystem + Clone> Chain<P> {
+    // 台帳の初期状態から、ブロックを1つも含まないチェーンを作る。初期状態のルートもアンカーとして使える
+    pub fn new(ledger: Ledger<P>) -> Self {
+        let root = ledger.tree().root();
+        Chain {
+            ledger,
+            blocks: Vec::new(),
+            recent_roots: VecDeque::from([root]),
+            anchor_window: DEFAULT_ANCHOR_WINDOW,
+        }
+    }
+
+    // アンカーとして使える直近のブロックの数を指定する
+    pub fn with_anchor_window(mut self, anchor_window: usize) -> Self {
+        self.anchor_window = anchor_window.max(1);
+        while self.recent_roots.len() > self.anchor_window {
+            self.recent_roots.pop_front();
+        }
+        self
+    }
+
+    pub fn ledger(&self) -> &Ledger<P> {
+        &self.ledger
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    // 先頭のブロックの高さ。ブロックがなければ 0
+    pub fn height(&self) -> u64 {
+        self.blocks.len() as u64
+    }
+
+    // 先頭のブロックのハッシュ。次のブロックの親になる
+    pub fn tip_hash(&self) -> BlockHash {
+        self.blocks
+            .last()
+            .map_or(BlockHash::GENESIS_PARENT, Block::hash)
+    }
+
+    // アンカーが直近のブロックのあとのツリーのルートかどうか
+    pub fn is_recent_anchor(&self, anchor: &Anchor) -> bool {
+        self.recent_roots.contains(anchor)
+    }
+
+    // トランザクションを順に適用し、手数料を fee_recipient へ入金したあとの台帳を作る。現在の台帳は変更しない
+    fn execute(
+        &self,
+        transactions: &[Transaction],
+        fee_recipient: TransparentAddress,
+    ) -> Result<Ledger<P>, BlockError> {
+        let mut ledger = self.ledger.clone();
+        for (index, transaction) in transactions.iter().enumerate() {
+            if !self.is_recent_anchor(&transaction.anchor) {
+                return Err(BlockError::StaleAnchor(index, transaction.anchor));
+            }
+            ledger
+                .apply(transaction)
+                .map_err(|err| BlockError::InvalidTransaction(index, err))?;
+        }
+        ledger
+            .collect_fees(fee_recipient)
+            .map_err(BlockError::FeeCollection)?;
+        Ok(ledger)
+    }
+
+    // 適用したあとの台帳から、次のブロックのヘッダを作る
+    fn next_header(
+        &self,
+        ledger: &Ledger<P>,
+        transactions: &[Transaction],
+        fee_recipient: TransparentAddress,
+    ) -> BlockHeader {
+        BlockHeader {
+            prev_hash: self.tip_hash(),
+            height: self.height() + 1,
+            tree_root: ledger.tree().root(),
+            nullifier_digest: ledger.nullifiers().digest(),
+            transactions_digest: transactions_digest(transactions),
+            fee_recipient,
+        }
+    }
+
+    // 適用したあとの台帳とブロックを確定し、アンカーとして使えるルートを進める
+    fn commit(&mut self, ledger: Ledger<P>, block: Block) {
+        self.ledger = ledger;
+        self.recent_roots.push_back(block.header.tree_root);
+        while self.recent_roots.len() > self.anchor_window {
+            self.recent_roots.pop_front();
+        }
+        self.blocks.push(block);
+    }
+
+    // トランザクションの列から、手数料を fee_recipient が受け取る次のブロックを組み立てる。チェーンには追加しない
+    pub fn build_block(
+        &self,
+        transactions: Vec<Transaction>,
+        fee_recipient: TransparentAddress,
+    ) -> Result<Block, BlockError> {
+        let ledger = self.execute(&transactions, fee_recipient)?;
+        Ok(Block {
+            header: self.next_header(&ledger, &transactions, fee_recipient),
+            transactions,
+        })
+    }
+
+    // 候補を順に試し、適用できるものだけを最大 max_transactions 個まで含めて次のブロックを組み立てる。
+    // アンカーが古い候補や、先に含めた候補と両立しない候補は飛ばす
+    pub fn select_block(
+        &self,
+        candidates: impl IntoIterator<Item = Transaction>,
+        fee_recipient: TransparentAddress,
+        max_transactions: usize,
+    ) -> Result<Block, BlockError> {
+        let mut ledger = self.ledger.clone();
+        let mut transactions = Vec::new();
+        for transaction in candidates {
+            if transactions.len() >= max_transactions {
+                break;
+            }
+            if self.is_recent_anchor(&transaction.anchor) && ledger.apply(&transaction).is_ok() {
+                transactions.push(transaction);
+            }
+        }
+        ledger
+            .collect_fees(fee_recipient)
+            .map_err(BlockError::FeeCollection)?;
+        Ok(Block {
+            header: self.next_header(&ledger, &transactions, fee_recipient),
+            transactions,
+        })
+    }
+
+    // 他で組み立てられたブロックを検証して追加する。先頭のブロックにつながり、
+    // トランザクションをすべて適用した結果がヘッダと一致しなければ、チェーンを一切変更せずに拒否する
+    pub fn apply_block(&mut self, block: &Block) -> Result<(), BlockError> {
+        let header = &block.header;
+        if header.height != self.height() + 1 {
+            return Err(BlockError::WrongHeight {
+                expected: self.height() + 1,
+                found: header.height,
+            });
+        }
+        if header.prev_hash != self.tip_hash() {
+            return Err(BlockError::UnknownParent(header.prev_hash));
+        }
+        if header.transactions_digest != transactions_digest(&block.transactions) {
+            return Err(BlockError::TransactionsDigestMismatch);
+        }
+
+        let ledger = self.execute(&block.transactions, header.fee_recipient)?;
+        if header.tree_root != ledger.tree().root() {
+            return Err(BlockError::TreeRootMismatch(header.tree_root));
+        }
+        if header.nullifier_digest != ledger.nullifiers().digest() {
+            return Err(BlockError::NullifierDigestMismatch);
+        }
+        self.commit(ledger, block.clone());
+        Ok(())
+    }
+
+    // トランザクションの列から、手数料を fee_recipient が受け取る次のブロックを組み立てて、そのままチェーンに追加する
+    pub fn produce_block(
+        &mut self,
+        transactions: Vec<Transaction>,
+        fee_recipient: TransparentAddress,
+    ) -> Result<&Block, BlockError> {
+        let ledger = self.execute(&transactions, fee_recipient)?;
+        let block = Block {
+            header: self.next_header(&ledger, &transactions, fee_recipient),
+            transactions,
+        };
+        self.commit(ledger, block);
+        Ok(self.blocks.last().expect("a block was just added"))
+    }
+}
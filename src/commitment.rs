@@ -0,0 +1,80 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::asset::AssetType;
+use crate::group::{Point, Scalar};
+use crate::keys::PaymentAddress;
+
+// ノートコミットメント。曲線上の点の32バイト圧縮表現
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoteCommitment(pub [u8; 32]);
+
+impl fmt::Debug for NoteCommitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NoteCommitment(")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// コミットメントに使う2つの生成元 (G, R)。互いの離散対数が分からないようハッシュから導出する
+fn generators() -> &'static (Point, Point) {
+    static GENERATORS: OnceLock<(Point, Point)> = OnceLock::new();
+    GENERATORS.get_or_init(|| {
+        (
+            Point::hash_to_point(b"MASPsim_NoteCmt", b"G"),
+            Point::hash_to_point(b"MASPsim_NoteCmt", b"R"),
+        )
+    })
+}
+
+// 資産タイプ、量、受取人のアドレスをブラインディング係数 rcm で隠したPedersen型のコミットメント
+// cm = [H(asset_type, amount, recipient)] G + [rcm] R
+pub fn commit_note(
+    asset_type: &AssetType,
+    amount: u64,
+    recipient: &PaymentAddress,
+    rcm: &Scalar,
+) -> NoteCommitment {
+    let message = Scalar::hash(
+        b"MASPsim_NoteMsg",
+        &[
+            &asset_type.to_bytes(),
+            &amount.to_le_bytes(),
+            &recipient.to_bytes(),
+        ],
+    );
+    let (g, r) = generators();
+    NoteCommitment((*g * message + *r * *rcm).to_bytes())
+}
+
+// 値コミットメント。資産タイプごとの生成元に量を掛けて、ブラインディング係数 rcv で隠す
+// cv = [value] V_asset + [rcv] R_cv
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValueCommitment(pub Point);
+
+// 資産タイプごとの値の生成元 V_asset。異なる資産の量が互いに打ち消し合わないよう資産タイプから導出する
+pub fn value_base(asset_type: &AssetType) -> Point {
+    Point::hash_to_point(b"MASPsim_ValueCmt", &asset_type.to_bytes())
+}
+
+// 値コミットメントのブラインディング係数の生成元 R_cv。バインディング署名の生成元も兼ねる
+pub fn value_randomness_base() -> &'static Point {
+    static BASE: OnceLock<Point> = OnceLock::new();
+    BASE.get_or_init(|| Point::hash_to_point(b"MASPsim_ValueCmt", b"r"))
+}
+
+impl ValueCommitment {
+    pub fn derive(asset_type: &AssetType, amount: u64, rcv: &Scalar) -> Self {
+        ValueCommitment(
+            value_base(asset_type) * Scalar::from_u64(amount) + *value_randomness_base() * *rcv,
+        )
+    }
+
+    // 公開された値の収支に対するブラインディングなしのコミットメント
+    pub fn public(asset_type: &AssetType, value: i64) -> Self {
+        ValueCommitment(value_base(asset_type) * Scalar::from_i64(value))
+    }
+}
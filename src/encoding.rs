@@ -0,0 +1,755 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::asset::{AssetInfo, AssetRegistry, AssetType};
+use crate::block::{Block, BlockHash, BlockHeader};
+use crate::commitment::{NoteCommitment, ValueCommitment};
+use crate::error::DecodeError;
+use crate::fee::FeeRule;
+use crate::group::{Point, Scalar};
+use crate::issuance::Issuance;
+use crate::keys::{Diversifier, PaymentAddress};
+use crate::ledger::LedgerSnapshot;
+use crate::note::Note;
+use crate::note_encryption::EncryptedNote;
+use crate::nullifier::{Nullifier, NullifierSet};
+use crate::proof::Proof;
+use crate::signature::Signature;
+use crate::transaction::{Output, Spend, Transaction, TxId};
+use crate::transparent::{
+    TransparentAccount, TransparentAddress, TransparentInput, TransparentLedger, TransparentOutput,
+};
+use crate::tree::Anchor;
+
+// 符号化の形式のバージョン。符号化したバイト列の先頭に置く
+pub const VERSION: u8 = 1;
+
+// 正規のバイナリ表現への符号化。整数はリトルエンディアンの固定長、可変長の値は u32 の長さを前に置き、
+// 写像と集合はキーの昇順に並べる。同じ値は常に同じバイト列になる
+pub trait Encode {
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+// 正規のバイナリ表現からの復号。正規でない表現は受け付けないので、復号できたバイト列は符号化し直すと一致する
+pub trait Decode: Sized {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError>;
+}
+
+// バージョンを付けて符号化する
+pub fn encode<T: Encode>(value: &T) -> Vec<u8> {
+    let mut out = vec![VERSION];
+    value.encode(&mut out);
+    out
+}
+
+// バージョンを確認して復号する。値のあとに余分なバイトが残っていれば拒否する
+pub fn decode<T: Decode>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let mut reader = Reader::new(bytes);
+    let version = reader.read_u8()?;
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    let value = T::decode(&mut reader)?;
+    if !reader.bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes(reader.bytes.len()));
+    }
+    Ok(value)
+}
+
+// 復号中のバイト列の残り
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if len > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    // 要素数やバイト長。残りのバイト数より大きければ、要素を読む前に拒否する
+    pub fn read_len(&mut self) -> Result<usize, DecodeError> {
+        let len = u32::from_le_bytes(self.read_array()?) as usize;
+        if len > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        Ok(len)
+    }
+
+    // 長さを前に置いたバイト列
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let len = self.read_len()?;
+        Ok(self.take(len)?.to_vec())
+    }
+}
+
+pub fn write_len(out: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("length fits in u32");
+    out.extend_from_slice(&len.to_le_bytes());
+}
+
+pub fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+impl<const N: usize> Encode for [u8; N] {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+}
+
+impl<const N: usize> Decode for [u8; N] {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        reader.read_array()
+    }
+}
+
+impl Encode for u8 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Decode for u8 {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        reader.read_u8()
+    }
+}
+
+impl Encode for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Decode for u64 {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(u64::from_le_bytes(reader.read_array()?))
+    }
+}
+
+impl Encode for i64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Decode for i64 {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(i64::from_le_bytes(reader.read_array()?))
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_bytes(out, self.as_bytes());
+    }
+}
+
+impl Decode for String {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        String::from_utf8(reader.read_bytes()?).map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
+
+// 省略可能な値。0 なら省略、1 なら値が続く
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(value) => {
+                out.push(1);
+                value.encode(out);
+            }
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        match reader.read_u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(reader)?)),
+            tag => Err(DecodeError::InvalidTag(tag)),
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.len());
+        for item in self {
+            item.encode(out);
+        }
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let len = reader.read_len()?;
+        (0..len).map(|_| T::decode(reader)).collect()
+    }
+}
+
+// キーの昇順。キーが直前のものより大きくなければ、並び順が違うか重複しているので拒否する
+impl<K: Encode, V: Encode> Encode for BTreeMap<K, V> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.len());
+        for (key, value) in self {
+            key.encode(out);
+            value.encode(out);
+        }
+    }
+}
+
+impl<K: Decode + Ord, V: Decode> Decode for BTreeMap<K, V> {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let len = reader.read_len()?;
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            let key = K::decode(reader)?;
+            if map.last_key_value().is_some_and(|(last, _)| *last >= key) {
+                return Err(DecodeError::UnorderedEntries);
+            }
+            map.insert(key, V::decode(reader)?);
+        }
+        Ok(map)
+    }
+}
+
+impl<T: Encode> Encode for BTreeSet<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_len(out, self.len());
+        for item in self {
+            item.encode(out);
+        }
+    }
+}
+
+impl<T: Decode + Ord> Decode for BTreeSet<T> {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let len = reader.read_len()?;
+        let mut set = BTreeSet::new();
+        for _ in 0..len {
+            let item = T::decode(reader)?;
+            if set.last().is_some_and(|last| *last >= item) {
+                return Err(DecodeError::UnorderedEntries);
+            }
+            set.insert(item);
+        }
+        Ok(set)
+    }
+}
+
+// 点は32バイトの圧縮表現。y 座標が p 以上のものや曲線上にないもの、素数位数の部分群に含まれないものは拒否する
+impl Encode for Point {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_bytes());
+    }
+}
+
+impl Decode for Point {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let point = Point::from_bytes(&reader.read_array()?).ok_or(DecodeError::InvalidPoint)?;
+        if !point.is_torsion_free() {
+            return Err(DecodeError::TorsionPoint);
+        }
+        Ok(point)
+    }
+}
+
+// スカラーは32バイトのリトルエンディアン。群の位数以上のものは拒否する
+impl Encode for Scalar {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_bytes());
+    }
+}
+
+impl Decode for Scalar {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Scalar::from_canonical_bytes(&reader.read_array()?).ok_or(DecodeError::NonCanonicalScalar)
+    }
+}
+
+impl Encode for AssetType {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_bytes());
+    }
+}
+
+impl Decode for AssetType {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(AssetType::from_bytes(reader.read_array()?))
+    }
+}
+
+// 32バイトの値をそのまま並べる識別子
+macro_rules! impl_bytes32 {
+    ($($name:ident),*) => {
+        $(
+            impl Encode for $name {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.0);
+                }
+            }
+
+            impl Decode for $name {
+                fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+                    Ok($name(reader.read_array()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_bytes32!(
+    Anchor,
+    BlockHash,
+    NoteCommitment,
+    Nullifier,
+    TransparentAddress,
+    TxId
+);
+
+impl Encode for PaymentAddress {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.diversifier.0.encode(out);
+        self.pk_d.encode(out);
+    }
+}
+
+impl Decode for PaymentAddress {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(PaymentAddress {
+            diversifier: Diversifier(reader.read_array()?),
+            pk_d: Point::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Note {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.asset_type.encode(out);
+        self.amount.encode(out);
+        self.recipient.encode(out);
+        self.rcm.encode(out);
+    }
+}
+
+impl Decode for Note {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Note {
+            asset_type: AssetType::decode(reader)?,
+            amount: u64::decode(reader)?,
+            recipient: PaymentAddress::decode(reader)?,
+            rcm: Scalar::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for ValueCommitment {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+    }
+}
+
+impl Decode for ValueCommitment {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(ValueCommitment(Point::decode(reader)?))
+    }
+}
+
+impl Encode for Signature {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.r.encode(out);
+        self.s.encode(out);
+    }
+}
+
+impl Decode for Signature {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Signature {
+            r: Point::decode(reader)?,
+            s: Scalar::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Proof {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_bytes(out, &self.0);
+    }
+}
+
+impl Decode for Proof {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Proof(reader.read_bytes()?))
+    }
+}
+
+impl Encode for EncryptedNote {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.cm.encode(out);
+        self.epk.encode(out);
+        write_bytes(out, &self.ciphertext);
+    }
+}
+
+impl Decode for EncryptedNote {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(EncryptedNote {
+            cm: NoteCommitment::decode(reader)?,
+            epk: Point::decode(reader)?,
+            ciphertext: reader.read_bytes()?,
+        })
+    }
+}
+
+impl Encode for Spend {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.cv.encode(out);
+        self.nullifier.encode(out);
+        self.rk.encode(out);
+        self.proof.encode(out);
+        self.spend_auth_sig.encode(out);
+    }
+}
+
+impl Decode for Spend {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Spend {
+            cv: ValueCommitment::decode(reader)?,
+            nullifier: Nullifier::decode(reader)?,
+            rk: Point::decode(reader)?,
+            proof: Proof::decode(reader)?,
+            spend_auth_sig: Signature::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Output {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.cv.encode(out);
+        self.encrypted.encode(out);
+        self.proof.encode(out);
+    }
+}
+
+impl Decode for Output {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Output {
+            cv: ValueCommitment::decode(reader)?,
+            encrypted: EncryptedNote::decode(reader)?,
+            proof: Proof::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for TransparentInput {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.address.encode(out);
+        self.public_key.encode(out);
+        self.asset_type.encode(out);
+        self.amount.encode(out);
+        self.nonce.encode(out);
+        self.signature.encode(out);
+    }
+}
+
+impl Decode for TransparentInput {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(TransparentInput {
+            address: TransparentAddress::decode(reader)?,
+            public_key: Point::decode(reader)?,
+            asset_type: AssetType::decode(reader)?,
+            amount: u64::decode(reader)?,
+            nonce: u64::decode(reader)?,
+            signature: Signature::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for TransparentOutput {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.address.encode(out);
+        self.asset_type.encode(out);
+        self.amount.encode(out);
+    }
+}
+
+impl Decode for TransparentOutput {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(TransparentOutput {
+            address: TransparentAddress::decode(reader)?,
+            asset_type: AssetType::decode(reader)?,
+            amount: u64::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Issuance {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.asset_type.encode(out);
+        self.amount.encode(out);
+        self.issuer.encode(out);
+        self.signature.encode(out);
+    }
+}
+
+impl Decode for Issuance {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Issuance {
+            asset_type: AssetType::decode(reader)?,
+            amount: u64::decode(reader)?,
+            issuer: Point::decode(reader)?,
+            signature: Signature::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Transaction {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.anchor.encode(out);
+        self.inputs.encode(out);
+        self.outputs.encode(out);
+        self.transparent_inputs.encode(out);
+        self.transparent_outputs.encode(out);
+        self.issuance.encode(out);
+        self.burns.encode(out);
+        self.value_balance.encode(out);
+        self.fee.encode(out);
+        self.binding_sig.encode(out);
+    }
+}
+
+impl Decode for Transaction {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Transaction {
+            anchor: Anchor::decode(reader)?,
+            inputs: Vec::decode(reader)?,
+            outputs: Vec::decode(reader)?,
+            transparent_inputs: Vec::decode(reader)?,
+            transparent_outputs: Vec::decode(reader)?,
+            issuance: Vec::decode(reader)?,
+            burns: BTreeMap::decode(reader)?,
+            value_balance: BTreeMap::decode(reader)?,
+            fee: u64::decode(reader)?,
+            binding_sig: Signature::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for AssetInfo {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.name.encode(out);
+        self.decimals.encode(out);
+        self.epoch.encode(out);
+    }
+}
+
+impl Decode for AssetInfo {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(AssetInfo {
+            name: String::decode(reader)?,
+            decimals: u8::decode(reader)?,
+            epoch: Option::decode(reader)?,
+        })
+    }
+}
+
+// 資産の識別子はメタデータから導出できるので、メタデータだけを識別子の昇順に並べ、発行者を続ける
+impl Encode for AssetRegistry {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let assets: BTreeMap<AssetType, &AssetInfo> = self
+            .assets()
+            .map(|(asset_type, info)| (*asset_type, info))
+            .collect();
+        write_len(out, assets.len());
+        for info in assets.values() {
+            info.encode(out);
+        }
+        let issuers: BTreeMap<AssetType, Point> = self
+            .issuers()
+            .map(|(asset_type, issuer)| (*asset_type, *issuer))
+            .collect();
+        issuers.encode(out);
+    }
+}
+
+impl Decode for AssetRegistry {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let mut registry = AssetRegistry::new();
+        let mut last = None;
+        for info in Vec::<AssetInfo>::decode(reader)? {
+            let asset_type = registry.register(&info.name, info.decimals, info.epoch);
+            if last.is_some_and(|last| last >= asset_type) {
+                return Err(DecodeError::UnorderedEntries);
+            }
+            last = Some(asset_type);
+        }
+        for (asset_type, issuer) in BTreeMap::<AssetType, Point>::decode(reader)? {
+            if !registry.set_issuer(&asset_type, issuer) {
+                return Err(DecodeError::UnregisteredIssuer(asset_type));
+            }
+        }
+        Ok(registry)
+    }
+}
+
+impl Encode for FeeRule {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.asset_type.encode(out);
+        self.base.encode(out);
+        self.per_action.encode(out);
+    }
+}
+
+impl Decode for FeeRule {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(FeeRule {
+            asset_type: AssetType::decode(reader)?,
+            base: u64::decode(reader)?,
+            per_action: u64::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for NullifierSet {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.iter().copied().collect::<BTreeSet<_>>().encode(out);
+    }
+}
+
+impl Decode for NullifierSet {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let mut nullifiers = NullifierSet::new();
+        for nullifier in BTreeSet::decode(reader)? {
+            nullifiers.insert(nullifier);
+        }
+        Ok(nullifiers)
+    }
+}
+
+impl Encode for TransparentAccount {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.balances.encode(out);
+        self.nonce.encode(out);
+    }
+}
+
+impl Decode for TransparentAccount {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(TransparentAccount {
+            balances: BTreeMap::decode(reader)?,
+            nonce: u64::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for TransparentLedger {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let accounts: BTreeMap<TransparentAddress, &TransparentAccount> = self
+            .accounts()
+            .map(|(address, account)| (*address, account))
+            .collect();
+        write_len(out, accounts.len());
+        for (address, account) in accounts {
+            address.encode(out);
+            account.encode(out);
+        }
+    }
+}
+
+impl Decode for TransparentLedger {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        let mut ledger = TransparentLedger::new();
+        for (address, account) in
+            BTreeMap::<TransparentAddress, TransparentAccount>::decode(reader)?
+        {
+            ledger.insert_account(address, account);
+        }
+        Ok(ledger)
+    }
+}
+
+impl Encode for LedgerSnapshot {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.registry.encode(out);
+        self.outputs.encode(out);
+        self.nullifiers.encode(out);
+        self.transparent.encode(out);
+        self.supply.encode(out);
+        self.fee_rule.encode(out);
+        self.unclaimed_fees.encode(out);
+    }
+}
+
+impl Decode for LedgerSnapshot {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(LedgerSnapshot {
+            registry: AssetRegistry::decode(reader)?,
+            outputs: Vec::decode(reader)?,
+            nullifiers: NullifierSet::decode(reader)?,
+            transparent: TransparentLedger::decode(reader)?,
+            supply: BTreeMap::decode(reader)?,
+            fee_rule: Option::decode(reader)?,
+            unclaimed_fees: u64::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for BlockHeader {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.prev_hash.encode(out);
+        self.height.encode(out);
+        self.tree_root.encode(out);
+        self.nullifier_digest.encode(out);
+        self.transactions_digest.encode(out);
+        self.fee_recipient.encode(out);
+    }
+}
+
+impl Decode for BlockHeader {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(BlockHeader {
+            prev_hash: BlockHash::decode(reader)?,
+            height: u64::decode(reader)?,
+            tree_root: Anchor::decode(reader)?,
+            nullifier_digest: reader.read_array()?,
+            transactions_digest: reader.read_array()?,
+            fee_recipient: TransparentAddress::decode(reader)?,
+        })
+    }
+}
+
+impl Encode for Block {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.header.encode(out);
+        self.transactions.encode(out);
+    }
+}
+
+impl Decode for Block {
+    fn decode(reader: &mut Reader) -> Result<Self, DecodeError> {
+        Ok(Block {
+            header: BlockHeader::decode(reader)?,
+            transactions: Vec::decode(reader)?,
+        })
+    }
+}
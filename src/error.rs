@@ -0,0 +1,296 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::asset::AssetType;
+use crate::block::BlockHash;
+use crate::commitment::NoteCommitment;
+use crate::nullifier::Nullifier;
+use crate::transaction::TxId;
+use crate::transparent::TransparentAddress;
+use crate::tree::Anchor;
+
+// トランザクションを拒否した理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    // 登録されていない資産タイプ
+    UnknownAssetType(AssetType),
+    // アンカーが過去のツリーのルートのどれとも一致しない
+    BadAnchor(Anchor),
+    // 入力の証明の検証に失敗した
+    InvalidSpendProof(Nullifier),
+    // 出力の証明の検証に失敗した
+    InvalidOutputProof(NoteCommitment),
+    // 公開済み、またはトランザクション内で重複したヌリファイア
+    DoubleSpend(Nullifier),
+    // バインディング署名の検証に失敗した。資産タイプごとの価値の収支が合わない
+    BadBindingSignature,
+    // 入力の支払い認証署名の検証に失敗した
+    BadSignature(Nullifier),
+    // 透明な入力の署名の検証に失敗した
+    BadTransparentSignature(TransparentAddress),
+    // 透明な入力のノンスがアカウントの現在のノンスと一致しない
+    StaleNonce(TransparentAddress),
+    // 透明なアカウントの残高が足りない
+    InsufficientTransparentBalance(TransparentAddress, AssetType),
+    // 透明なアカウントの残高が表現できる範囲を超える
+    TransparentBalanceOverflow(TransparentAddress, AssetType),
+    // 透明な入力、シールドされたプールから出ていく量、ミントの合計が、透明な出力、バーン、手数料の合計と一致しない
+    ValueImbalance(AssetType),
+    // 手数料が規則の最低額に足りない
+    InsufficientFee { required: u64, paid: u64 },
+    // 手数料の資産タイプが決まっていない台帳で手数料を払おうとした
+    NoFeeAsset,
+    // ミントが資産の発行者の鍵で署名されていない
+    UnauthorizedIssuance(AssetType),
+    // 総供給量が表現できる範囲を超える
+    SupplyOverflow(AssetType),
+    // 総供給量を超える量を破棄しようとした
+    BurnExceedsSupply(AssetType),
+    // 台帳が預かっている手数料が表現できる範囲を超える
+    FeeOverflow,
+    // 出力を追記するだけの空きがコミットメントツリーにない
+    TreeFull,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnknownAssetType(asset_type) => {
+                write!(f, "unknown asset type {:?}", asset_type)
+            }
+            ValidationError::BadAnchor(anchor) => write!(f, "unknown anchor {:?}", anchor),
+            ValidationError::InvalidSpendProof(nullifier) => {
+                write!(f, "invalid spend proof for {:?}", nullifier)
+            }
+            ValidationError::InvalidOutputProof(cm) => {
+                write!(f, "invalid output proof for {:?}", cm)
+            }
+            ValidationError::DoubleSpend(nullifier) => write!(f, "double spend of {:?}", nullifier),
+            ValidationError::BadBindingSignature => {
+                write!(f, "invalid binding signature, value is not balanced")
+            }
+            ValidationError::BadSignature(nullifier) => {
+                write!(
+                    f,
+                    "invalid spend authorization signature for {:?}",
+                    nullifier
+                )
+            }
+            ValidationError::BadTransparentSignature(address) => {
+                write!(f, "invalid transparent signature for {:?}", address)
+            }
+            ValidationError::StaleNonce(address) => write!(f, "stale nonce for {:?}", address),
+            ValidationError::InsufficientTransparentBalance(address, asset_type) => {
+                write!(
+                    f,
+                    "insufficient balance of {:?} in {:?}",
+                    asset_type, address
+                )
+            }
+            ValidationError::TransparentBalanceOverflow(address, asset_type) => {
+                write!(f, "balance of {:?} in {:?} overflows", asset_type, address)
+            }
+            ValidationError::ValueImbalance(asset_type) => {
+                write!(f, "inputs and outputs of {:?} do not balance", asset_type)
+            }
+            ValidationError::InsufficientFee { required, paid } => {
+                write!(f, "insufficient fee: required {}, paid {}", required, paid)
+            }
+            ValidationError::NoFeeAsset => write!(f, "no fee asset is configured"),
+            ValidationError::UnauthorizedIssuance(asset_type) => {
+                write!(
+                    f,
+                    "issuance of {:?} not authorized by its issuer",
+                    asset_type
+                )
+            }
+            ValidationError::SupplyOverflow(asset_type) => {
+                write!(f, "supply of {:?} overflows", asset_type)
+            }
+            ValidationError::BurnExceedsSupply(asset_type) => {
+                write!(f, "burn of {:?} exceeds its supply", asset_type)
+            }
+            ValidationError::FeeOverflow => write!(f, "unclaimed fees overflow"),
+            ValidationError::TreeFull => write!(f, "commitment tree is full"),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+// トランザクションを組み立てられなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    // 支払う人の未消費のノートが足りない
+    InsufficientFunds {
+        asset_type: AssetType,
+        required: u64,
+        available: u64,
+    },
+    // 量の合計が表現できる範囲を超えた
+    AmountOverflow(AssetType),
+    // 支払う人のウォレットが、送信者と同じツリーのルートまで同期していない
+    UnsyncedPayer(String),
+    // 証拠が命題を満たさず、証明を作成できなかった
+    ProofFailed,
+    // 手数料の量を指定したが、手数料の資産タイプを決める規則がない
+    NoFeeRule,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InsufficientFunds {
+                asset_type,
+                required,
+                available,
+            } => write!(
+                f,
+                "insufficient funds of {:?}: required {}, available {}",
+                asset_type, required, available
+            ),
+            BuildError::AmountOverflow(asset_type) => {
+                write!(f, "amount of {:?} overflows", asset_type)
+            }
+            BuildError::UnsyncedPayer(id) => {
+                write!(f, "{} is not synced to the sender's tree root", id)
+            }
+            BuildError::ProofFailed => write!(f, "witness does not satisfy the statement"),
+            BuildError::NoFeeRule => write!(f, "fee amount given without a fee rule"),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+// バイト列を復号できなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    // 値の途中でバイト列が終わった
+    UnexpectedEnd,
+    // 対応していない形式のバージョン
+    UnsupportedVersion(u8),
+    // 値のあとに余分なバイトが残っている
+    TrailingBytes(usize),
+    // 真偽値や省略可能な値の印が 0 でも 1 でもない
+    InvalidTag(u8),
+    // 曲線上の点の正規の表現ではない
+    InvalidPoint,
+    // 点が素数位数の部分群に含まれず、位数の小さい成分を持つ
+    TorsionPoint,
+    // スカラーが群の位数以上で、正規の表現ではない
+    NonCanonicalScalar,
+    // 文字列が UTF-8 ではない
+    InvalidUtf8,
+    // 集合や写像の要素がキーの昇順に並んでいないか、重複している
+    UnorderedEntries,
+    // 登録されていない資産に発行者が紐付いている
+    UnregisteredIssuer(AssetType),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported encoding version {}", version)
+            }
+            DecodeError::TrailingBytes(len) => write!(f, "{} trailing bytes", len),
+            DecodeError::InvalidTag(tag) => write!(f, "invalid tag {}", tag),
+            DecodeError::InvalidPoint => write!(f, "invalid point encoding"),
+            DecodeError::TorsionPoint => write!(f, "point is not in the prime-order subgroup"),
+            DecodeError::NonCanonicalScalar => write!(f, "non-canonical scalar encoding"),
+            DecodeError::InvalidUtf8 => write!(f, "string is not valid utf-8"),
+            DecodeError::UnorderedEntries => {
+                write!(f, "entries are not in strictly ascending order")
+            }
+            DecodeError::UnregisteredIssuer(asset_type) => {
+                write!(f, "issuer for unregistered asset type {:?}", asset_type)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+// ブロックを拒否した理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockError {
+    // 高さが次のブロックの高さと一致しない
+    WrongHeight { expected: u64, found: u64 },
+    // 親のハッシュが現在の先頭のブロックと一致しない
+    UnknownParent(BlockHash),
+    // 位置 index のトランザクションのアンカーが、直近のブロックのあとのツリーのルートのどれとも一致しない
+    StaleAnchor(usize, Anchor),
+    // 位置 index のトランザクションの検証に失敗した
+    InvalidTransaction(usize, ValidationError),
+    // ヘッダのトランザクションのダイジェストが、含まれるトランザクションと一致しない
+    TransactionsDigestMismatch,
+    // ヘッダのツリーのルートが、ブロックを適用したあとのルートと一致しない
+    TreeRootMismatch(Anchor),
+    // ヘッダのヌリファイアの集合のダイジェストが、ブロックを適用したあとの集合と一致しない
+    NullifierDigestMismatch,
+    // 手数料をブロックを作った人の透明なアカウントへ入金できない
+    FeeCollection(ValidationError),
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockError::WrongHeight { expected, found } => {
+                write!(
+                    f,
+                    "wrong block height: expected {}, found {}",
+                    expected, found
+                )
+            }
+            BlockError::UnknownParent(hash) => write!(f, "unknown parent block {:?}", hash),
+            BlockError::StaleAnchor(index, anchor) => {
+                write!(f, "transaction {} uses stale anchor {:?}", index, anchor)
+            }
+            BlockError::InvalidTransaction(index, err) => {
+                write!(f, "transaction {} is invalid: {}", index, err)
+            }
+            BlockError::TransactionsDigestMismatch => {
+                write!(f, "transactions do not match the header")
+            }
+            BlockError::TreeRootMismatch(root) => {
+                write!(f, "tree root {:?} does not match the header", root)
+            }
+            BlockError::NullifierDigestMismatch => {
+                write!(f, "nullifier set does not match the header")
+            }
+            BlockError::FeeCollection(err) => write!(f, "cannot collect fees: {}", err),
+        }
+    }
+}
+
+impl Error for BlockError {}
+
+// トランザクションをメモリプールに受け付けなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MempoolError {
+    // 同じトランザクションが既に保留中
+    AlreadyPending(TxId),
+    // ヌリファイアが保留中の別のトランザクションと衝突する
+    Conflict(Nullifier, TxId),
+    // アンカーが直近のブロックのあとのツリーのルートではない
+    StaleAnchor(Anchor),
+    // 現在の台帳に対する検証に失敗した
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for MempoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MempoolError::AlreadyPending(txid) => write!(f, "{:?} is already pending", txid),
+            MempoolError::Conflict(nullifier, txid) => {
+                write!(f, "{:?} conflicts with pending {:?}", nullifier, txid)
+            }
+            MempoolError::StaleAnchor(anchor) => write!(f, "stale anchor {:?}", anchor),
+            MempoolError::Invalid(err) => write!(f, "invalid transaction: {}", err),
+        }
+    }
+}
+
+impl Error for MempoolError {}
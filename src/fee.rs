@@ -0,0 +1,32 @@
+use crate::asset::AssetType;
+use crate::transaction::Transaction;
+
+// 手数料の規則。手数料を払う資産タイプと、トランザクションの大きさに応じた最低額
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeRule {
+    pub asset_type: AssetType,
+    pub base: u64,       // トランザクションごとの額
+    pub per_action: u64, // 入力と出力（透明なものを含む）1つごとの額
+}
+
+impl FeeRule {
+    pub fn new(asset_type: AssetType, base: u64, per_action: u64) -> Self {
+        FeeRule {
+            asset_type,
+            base,
+            per_action,
+        }
+    }
+
+    // 入力と出力の数が actions のトランザクションの最低額
+    pub fn minimum(&self, actions: usize) -> u64 {
+        self.per_action
+            .saturating_mul(actions as u64)
+            .saturating_add(self.base)
+    }
+
+    // トランザクションの最低額
+    pub fn minimum_for(&self, transaction: &Transaction) -> u64 {
+        self.minimum(transaction.actions())
+    }
+}
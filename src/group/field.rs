@@ -0,0 +1,193 @@
+// 素体 GF(2^255 - 19) の元。51ビットずつ5つのリムに分けて保持する
+#[derive(Clone, Copy, Debug)]
+pub struct FieldElement([u64; 5]);
+
+const MASK: u64 = (1 << 51) - 1;
+
+// p - 2 （逆元の計算に使う指数）
+const P_MINUS_2: [u8; 32] = exponent(0xeb, 0x7f);
+// (p - 5) / 8 （平方根の計算に使う指数）
+const P_MINUS_5_DIV_8: [u8; 32] = exponent(0xfd, 0x0f);
+// (p - 1) / 4 （-1 の平方根の計算に使う指数）
+const P_MINUS_1_DIV_4: [u8; 32] = exponent(0xfb, 0x1f);
+
+// 先頭と末尾以外のバイトがすべて 0xff のリトルエンディアンの指数を作る
+const fn exponent(low: u8, high: u8) -> [u8; 32] {
+    let mut bytes = [0xff; 32];
+    bytes[0] = low;
+    bytes[31] = high;
+    bytes
+}
+
+impl FieldElement {
+    pub const ZERO: FieldElement = FieldElement([0; 5]);
+    pub const ONE: FieldElement = FieldElement([1, 0, 0, 0, 0]);
+
+    pub fn from_u64(value: u64) -> Self {
+        FieldElement([value & MASK, value >> 51, 0, 0, 0])
+    }
+
+    // 32バイトのリトルエンディアン表現から読み込む。最上位ビットは無視する
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let load = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        FieldElement([
+            load(0) & MASK,
+            (load(6) >> 3) & MASK,
+            (load(12) >> 6) & MASK,
+            (load(19) >> 1) & MASK,
+            (load(24) >> 12) & MASK,
+        ])
+    }
+
+    // 正規化した32バイトのリトルエンディアン表現
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut limbs = self.carry().0;
+        let mut q = (limbs[0] + 19) >> 51;
+        for limb in &limbs[1..] {
+            q = (limb + q) >> 51;
+        }
+        limbs[0] += 19 * q;
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> 51;
+            limbs[i] &= MASK;
+        }
+        limbs[4] &= MASK;
+
+        let mut out = [0u8; 32];
+        let mut acc: u128 = 0;
+        let mut bits = 0;
+        let mut index = 0;
+        for limb in limbs {
+            acc |= u128::from(limb) << bits;
+            bits += 51;
+            while bits >= 8 {
+                out[index] = acc as u8;
+                acc >>= 8;
+                bits -= 8;
+                index += 1;
+            }
+        }
+        out[index] = acc as u8;
+        out
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.to_bytes() == [0; 32]
+    }
+
+    // 正規化した表現の最下位ビットが1であれば負とみなす
+    pub fn is_negative(self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+
+    pub fn add(self, other: Self) -> Self {
+        let mut limbs = self.0;
+        for (limb, rhs) in limbs.iter_mut().zip(other.0) {
+            *limb += rhs;
+        }
+        FieldElement(limbs).carry()
+    }
+
+    pub fn sub(self, other: Self) -> Self {
+        // 2p を足してから引くことで桁借りを避ける
+        let two_p = [
+            0x000f_ffff_ffff_ffda,
+            0x000f_ffff_ffff_fffe,
+            0x000f_ffff_ffff_fffe,
+            0x000f_ffff_ffff_fffe,
+            0x000f_ffff_ffff_fffe,
+        ];
+        let mut limbs = self.0;
+        for i in 0..5 {
+            limbs[i] = limbs[i] + two_p[i] - other.0[i];
+        }
+        FieldElement(limbs).carry()
+    }
+
+    pub fn neg(self) -> Self {
+        FieldElement::ZERO.sub(self)
+    }
+
+    pub fn mul(self, other: Self) -> Self {
+        let a = self.0;
+        let b = other.0;
+        let m = |x: u64, y: u64| u128::from(x) * u128::from(y);
+        let b1 = b[1] * 19;
+        let b2 = b[2] * 19;
+        let b3 = b[3] * 19;
+        let b4 = b[4] * 19;
+
+        let c0 = m(a[0], b[0]) + m(a[4], b1) + m(a[3], b2) + m(a[2], b3) + m(a[1], b4);
+        let mut c1 = m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2) + m(a[3], b3) + m(a[2], b4);
+        let mut c2 = m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3) + m(a[3], b4);
+        let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4);
+        let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+        c1 += c0 >> 51;
+        c2 += c1 >> 51;
+        c3 += c2 >> 51;
+        c4 += c3 >> 51;
+        let mut limbs = [
+            (c0 as u64) & MASK,
+            (c1 as u64) & MASK,
+            (c2 as u64) & MASK,
+            (c3 as u64) & MASK,
+            (c4 as u64) & MASK,
+        ];
+        limbs[0] += ((c4 >> 51) as u64) * 19;
+        FieldElement(limbs).carry()
+    }
+
+    pub fn square(self) -> Self {
+        self.mul(self)
+    }
+
+    // リトルエンディアンの指数によるべき乗
+    fn pow(self, exponent: &[u8; 32]) -> Self {
+        let mut result = FieldElement::ONE;
+        for byte in exponent.iter().rev() {
+            for bit in (0..8).rev() {
+                result = result.square();
+                if (byte >> bit) & 1 == 1 {
+                    result = result.mul(self);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn invert(self) -> Self {
+        self.pow(&P_MINUS_2)
+    }
+
+    // u / v の平方根を求める。存在しなければ None
+    pub fn sqrt_ratio(u: Self, v: Self) -> Option<Self> {
+        let v3 = v.square().mul(v);
+        let v7 = v3.square().mul(v);
+        let mut x = u.mul(v3).mul(u.mul(v7).pow(&P_MINUS_5_DIV_8));
+
+        let check = v.mul(x.square());
+        if check.sub(u).is_zero() {
+            Some(x)
+        } else if check.add(u).is_zero() {
+            x = x.mul(FieldElement::from_u64(2).pow(&P_MINUS_1_DIV_4));
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    // 各リムを51ビットに収まるよう繰り上げる
+    fn carry(self) -> Self {
+        let mut limbs = self.0;
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> 51;
+            limbs[i] &= MASK;
+        }
+        limbs[0] += (limbs[4] >> 51) * 19;
+        limbs[4] &= MASK;
+        limbs[1] += limbs[0] >> 51;
+        limbs[0] &= MASK;
+        FieldElement(limbs)
+    }
+}
@@ -0,0 +1,7 @@
+// Ed25519 曲線の素数位数部分群をゼロから実装したもの。コミットメントや鍵の土台として使う
+mod field;
+mod point;
+mod scalar;
+
+pub use point::Point;
+pub use scalar::Scalar;
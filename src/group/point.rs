@@ -0,0 +1,181 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::OnceLock;
+
+use super::field::FieldElement;
+use super::scalar::Scalar;
+use crate::hash::hash32;
+
+// ねじれEdwards曲線 -x^2 + y^2 = 1 + d x^2 y^2 （Ed25519）上の点。拡張座標 (X : Y : Z : T) で保持する
+#[derive(Clone, Copy)]
+pub struct Point {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement,
+}
+
+// 曲線の係数 d = -121665 / 121666
+fn curve_d() -> FieldElement {
+    static D: OnceLock<FieldElement> = OnceLock::new();
+    *D.get_or_init(|| {
+        FieldElement::from_u64(121665)
+            .neg()
+            .mul(FieldElement::from_u64(121666).invert())
+    })
+}
+
+impl Point {
+    pub const IDENTITY: Point = Point {
+        x: FieldElement::ZERO,
+        y: FieldElement::ONE,
+        z: FieldElement::ONE,
+        t: FieldElement::ZERO,
+    };
+
+    // ドメイン分離したハッシュ値から、離散対数が誰にも分からない部分群の点を導出する
+    pub fn hash_to_point(personal: &[u8], message: &[u8]) -> Self {
+        for counter in 0u32.. {
+            let candidate = hash32(personal, &[message, &counter.to_le_bytes()]);
+            if let Some(point) = Point::from_bytes(&candidate) {
+                // 余因子 8 を掛けて素数位数の部分群に移す
+                let point = point.double().double().double();
+                if point != Point::IDENTITY {
+                    return point;
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    // 圧縮表現 (y 座標と x の符号ビット) から復元する
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let sign = bytes[31] >> 7;
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+        let y = FieldElement::from_bytes(&y_bytes);
+        if y.to_bytes() != y_bytes {
+            return None;
+        }
+
+        let y2 = y.square();
+        let u = y2.sub(FieldElement::ONE);
+        let v = curve_d().mul(y2).add(FieldElement::ONE);
+        let mut x = FieldElement::sqrt_ratio(u, v)?;
+        if x.is_zero() && sign == 1 {
+            return None;
+        }
+        if u8::from(x.is_negative()) != sign {
+            x = x.neg();
+        }
+        Some(Point {
+            x,
+            y,
+            z: FieldElement::ONE,
+            t: x.mul(y),
+        })
+    }
+
+    // 32バイトの圧縮表現
+    pub fn to_bytes(self) -> [u8; 32] {
+        let z_inv = self.z.invert();
+        let x = self.x.mul(z_inv);
+        let y = self.y.mul(z_inv);
+        let mut bytes = y.to_bytes();
+        bytes[31] |= u8::from(x.is_negative()) << 7;
+        bytes
+    }
+
+    pub fn double(self) -> Self {
+        self + self
+    }
+
+    // 素数位数 l の部分群に含まれるか。[l] P = [l - 1] P + P が単位元になるかで判定する
+    pub fn is_torsion_free(self) -> bool {
+        self.scalar_mul(&-Scalar::from_u64(1)) + self == Point::IDENTITY
+    }
+
+    // スカラー倍（二進法）
+    fn scalar_mul(self, scalar: &Scalar) -> Self {
+        let mut result = Point::IDENTITY;
+        for bit in scalar.bits().rev() {
+            result = result.double();
+            if bit {
+                result = result + self;
+            }
+        }
+        result
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    // 統一加算公式（a = -1 の拡張座標）
+    fn add(self, other: Point) -> Point {
+        let two_d = curve_d().add(curve_d());
+        let a = self.y.sub(self.x).mul(other.y.sub(other.x));
+        let b = self.y.add(self.x).mul(other.y.add(other.x));
+        let c = self.t.mul(two_d).mul(other.t);
+        let d = self.z.add(self.z).mul(other.z);
+        let e = b.sub(a);
+        let f = d.sub(c);
+        let g = d.add(c);
+        let h = b.add(a);
+        Point {
+            x: e.mul(f),
+            y: g.mul(h),
+            z: f.mul(g),
+            t: e.mul(h),
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    // (x, y) の逆元は (-x, y)
+    fn neg(self) -> Point {
+        Point {
+            x: self.x.neg(),
+            y: self.y,
+            z: self.z,
+            t: self.t.neg(),
+        }
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        self.add(other.neg())
+    }
+}
+
+impl Mul<Scalar> for Point {
+    type Output = Point;
+
+    fn mul(self, scalar: Scalar) -> Point {
+        self.scalar_mul(&scalar)
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Self) -> bool {
+        self.x.mul(other.z).sub(other.x.mul(self.z)).is_zero()
+            && self.y.mul(other.z).sub(other.y.mul(self.z)).is_zero()
+    }
+}
+
+impl Eq for Point {}
+
+impl fmt::Debug for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Point(")?;
+        for byte in self.to_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::hash::hash64;
+use crate::rng;
+
+// 群の位数 l = 2^252 + 27742317777372353535851937790883648493 を法とするスカラー
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Scalar([u64; 4]);
+
+// l のリトルエンディアン表現（64ビットずつ）
+const L: [u64; 4] = [
+    0x5812_631a_5cf5_d3ed,
+    0x14de_f9de_a2f7_9cd6,
+    0x0000_0000_0000_0000,
+    0x1000_0000_0000_0000,
+];
+
+impl Scalar {
+    pub const ZERO: Scalar = Scalar([0; 4]);
+
+    pub fn from_u64(value: u64) -> Self {
+        Scalar([value, 0, 0, 0])
+    }
+
+    // 符号付きの値。負の値は l - |value| になる
+    pub fn from_i64(value: i64) -> Self {
+        let magnitude = Scalar::from_u64(value.unsigned_abs());
+        if value < 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    // 64バイトの一様な乱数（ハッシュ値など）を l で剰余して得る
+    pub fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+        let mut limbs = [0u64; 8];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Scalar::reduce_wide(limbs)
+    }
+
+    // ドメイン分離したハッシュ値からスカラーを導出
+    pub fn hash(personal: &[u8], parts: &[&[u8]]) -> Self {
+        Scalar::from_bytes_wide(&hash64(personal, parts))
+    }
+
+    // 一様ランダムなスカラーを生成
+    pub fn random() -> Self {
+        let mut bytes = [0u8; 64];
+        rng::fill_bytes(&mut bytes);
+        Scalar::from_bytes_wide(&bytes)
+    }
+
+    // 32バイトのリトルエンディアン表現から読み込む。l 以上の値は正規の表現ではないので拒否する
+    pub fn from_canonical_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let mut limbs = [0u64; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        less_than(&limbs, &L).then_some(Scalar(limbs))
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (chunk, limb) in out.chunks_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        out
+    }
+
+    // 下位ビットから順にスカラーのビットを返す
+    pub fn bits(self) -> impl DoubleEndedIterator<Item = bool> {
+        (0..256).map(move |i| (self.0[i / 64] >> (i % 64)) & 1 == 1)
+    }
+
+    // 512ビットの値を上位ビットから1ビットずつ取り込み、l で剰余する
+    fn reduce_wide(limbs: [u64; 8]) -> Self {
+        let mut r = [0u64; 4];
+        for i in (0..512).rev() {
+            let bit = (limbs[i / 64] >> (i % 64)) & 1;
+            // r < l < 2^253 なので2倍してもあふれない
+            for j in (1..4).rev() {
+                r[j] = (r[j] << 1) | (r[j - 1] >> 63);
+            }
+            r[0] = (r[0] << 1) | bit;
+            if !less_than(&r, &L) {
+                let mut borrow = 0u64;
+                for j in 0..4 {
+                    let (diff, b1) = r[j].overflowing_sub(L[j]);
+                    let (diff, b2) = diff.overflowing_sub(borrow);
+                    r[j] = diff;
+                    borrow = u64::from(b1 || b2);
+                }
+            }
+        }
+        Scalar(r)
+    }
+}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    fn add(self, other: Scalar) -> Scalar {
+        let mut limbs = [0u64; 8];
+        let mut carry = 0u128;
+        for (limb, (a, b)) in limbs.iter_mut().zip(self.0.iter().zip(other.0)) {
+            let sum = u128::from(*a) + u128::from(b) + carry;
+            *limb = sum as u64;
+            carry = sum >> 64;
+        }
+        limbs[4] = carry as u64;
+        Scalar::reduce_wide(limbs)
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        if self == Scalar::ZERO {
+            return self;
+        }
+        // l - self （self < l なので桁借りは最上位で止まる）
+        let mut limbs = [0u64; 4];
+        let mut borrow = 0u64;
+        for (limb, (l, a)) in limbs.iter_mut().zip(L.iter().zip(self.0)) {
+            let (diff, b1) = l.overflowing_sub(a);
+            let (diff, b2) = diff.overflowing_sub(borrow);
+            *limb = diff;
+            borrow = u64::from(b1 || b2);
+        }
+        Scalar(limbs)
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Scalar;
+
+    fn sub(self, other: Scalar) -> Scalar {
+        self.add(other.neg())
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Scalar;
+
+    // 筆算で512ビットの積を求めてから l で剰余する
+    fn mul(self, other: Scalar) -> Scalar {
+        let mut limbs = [0u64; 8];
+        for (i, a) in self.0.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, b) in other.0.iter().enumerate() {
+                let t = u128::from(*a) * u128::from(*b) + u128::from(limbs[i + j]) + carry;
+                limbs[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            limbs[i + 4] = carry as u64;
+        }
+        Scalar::reduce_wide(limbs)
+    }
+}
+
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+    false
+}
+
+impl fmt::Debug for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.to_bytes();
+        write!(f, "Scalar(")?;
+        for byte in bytes.iter().rev() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,214 @@
+// BLAKE2b (RFC 7693) の実装。パーソナライゼーションでドメインを分離して使う
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+#[derive(Clone)]
+pub struct Blake2b {
+    h: [u64; 8],
+    counter: u128,
+    buf: [u8; 128],
+    buf_len: usize,
+    out_len: usize,
+}
+
+impl Blake2b {
+    // 出力長（1〜64バイト）と16バイトまでのパーソナライゼーションを指定して初期化
+    pub fn new(out_len: usize, personal: &[u8]) -> Self {
+        assert!((1..=64).contains(&out_len), "invalid BLAKE2b output length");
+        assert!(personal.len() <= 16, "BLAKE2b personalization too long");
+        let mut padded = [0u8; 16];
+        padded[..personal.len()].copy_from_slice(personal);
+
+        let mut h = IV;
+        h[0] ^= 0x0101_0000 ^ out_len as u64;
+        h[6] ^= u64::from_le_bytes(padded[..8].try_into().unwrap());
+        h[7] ^= u64::from_le_bytes(padded[8..].try_into().unwrap());
+        Blake2b {
+            h,
+            counter: 0,
+            buf: [0; 128],
+            buf_len: 0,
+            out_len,
+        }
+    }
+
+    // 入力データを追加
+    pub fn update(&mut self, mut data: &[u8]) -> &mut Self {
+        while !data.is_empty() {
+            // 最後のブロックは finalize で圧縮するため、バッファが満杯でも次の入力があるまで保持する
+            if self.buf_len == 128 {
+                self.counter += 128;
+                let block = self.buf;
+                self.compress(&block, false);
+                self.buf_len = 0;
+            }
+            let take = (128 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + take].copy_from_slice(&data[..take]);
+            self.buf_len += take;
+            data = &data[take..];
+        }
+        self
+    }
+
+    // ハッシュ値を計算。先頭の out_len バイトが有効な出力
+    pub fn finalize(mut self) -> [u8; 64] {
+        self.counter += self.buf_len as u128;
+        self.buf[self.buf_len..].fill(0);
+        let block = self.buf;
+        self.compress(&block, true);
+
+        let mut out = [0u8; 64];
+        for (chunk, word) in out.chunks_mut(8).zip(self.h.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out[self.out_len..].fill(0);
+        out
+    }
+
+    fn compress(&mut self, block: &[u8; 128], last: bool) {
+        let mut m = [0u64; 16];
+        for (word, chunk) in m.iter_mut().zip(block.chunks(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let mut v = [0u64; 16];
+        v[..8].copy_from_slice(&self.h);
+        v[8..].copy_from_slice(&IV);
+        v[12] ^= self.counter as u64;
+        v[13] ^= (self.counter >> 64) as u64;
+        if last {
+            v[14] = !v[14];
+        }
+
+        for s in &SIGMA {
+            mix(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+            mix(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+            mix(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+            mix(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+            mix(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+            mix(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+            mix(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+            mix(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+        }
+
+        for i in 0..8 {
+            self.h[i] ^= v[i] ^ v[i + 8];
+        }
+    }
+}
+
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+// 複数の入力を連結した32バイトのハッシュ値を計算
+pub fn hash32(personal: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Blake2b::new(32, personal);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize()[..32].try_into().unwrap()
+}
+
+// 複数の入力を連結した64バイトのハッシュ値を計算
+pub fn hash64(personal: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut hasher = Blake2b::new(64, personal);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    // i mod 251 を並べた n バイトの入力
+    fn counting(n: usize) -> Vec<u8> {
+        (0..n).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn blake2b_matches_known_answers() {
+        // RFC 7693 付録 A
+        assert_eq!(
+            hex(&hash64(b"", &[b"abc"])),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+
+        // パーソナライゼーション付きで、128バイトのブロック境界の前後を含む
+        let expected = [
+            (
+                0,
+                "82a4dea7d33ad3e0a7d854c68cefc79a159f0bbcb388abb2dc6ba1033dad8784",
+            ),
+            (
+                127,
+                "c005c153618d1d2d9f14a2f2301f75071852811a97c4c8c98a03927e81f4d0a3",
+            ),
+            (
+                128,
+                "bdb393bda9c8de9319b0dd95c185b197112a76afad51d934d8cbc5e39f06ae68",
+            ),
+            (
+                129,
+                "ff0a997c172d10c1cf4c692a9462debccc7dc54cf20f356b6dc4d5b980551f94",
+            ),
+            (
+                256,
+                "9b7fc048f0a4cc31e3ca2589dcbb0f0de9c70ec2e40f5f396ac6647adee92553",
+            ),
+        ];
+        for (len, digest) in expected {
+            assert_eq!(hex(&hash32(b"MASPsim_TxId", &[&counting(len)])), digest);
+        }
+    }
+
+    #[test]
+    fn blake2b_does_not_depend_on_how_input_is_split() {
+        let data: Vec<u8> = (0..200).collect();
+        let digest = "e08a86de814847823821dfc5381091e2c82a845246138bb6413145e5b95b7c04\
+                      08d0a934dff6b8fa7b9adbdeffc9e43e5832b89f77200c171b1bf8aab2b1f95b";
+        for split in [0, 1, 127, 128, 129, 200] {
+            let mut hasher = Blake2b::new(64, b"0123456789abcdef");
+            hasher.update(&data[..split]).update(&data[split..]);
+            assert_eq!(hex(&hasher.finalize()), digest);
+        }
+    }
+}
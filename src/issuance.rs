@@ -0,0 +1,45 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::asset::AssetType;
+use crate::group::{Point, Scalar};
+use crate::signature::Signature;
+
+// 発行者の署名に使う生成元
+pub fn issuance_base() -> &'static Point {
+    static BASE: OnceLock<Point> = OnceLock::new();
+    BASE.get_or_init(|| Point::hash_to_point(b"MASPsim_KeyGen", b"Issuance"))
+}
+
+// 資産の発行者の秘密鍵。公開鍵を資産の登録に紐付けると、その資産をミントできる
+#[derive(Clone)]
+pub struct IssuanceKey(Scalar);
+
+impl IssuanceKey {
+    pub fn random() -> Self {
+        IssuanceKey(Scalar::random())
+    }
+
+    pub fn public_key(&self) -> Point {
+        *issuance_base() * self.0
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature::sign(issuance_base(), &self.0, message)
+    }
+}
+
+impl fmt::Debug for IssuanceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IssuanceKey(..)")
+    }
+}
+
+// ミント。発行者の署名で認可された量だけ、資産が新しく作られてトランザクションに入ってくる
+#[derive(Debug, Clone)]
+pub struct Issuance {
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub issuer: Point, // 発行者の公開鍵
+    pub signature: Signature,
+}
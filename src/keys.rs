@@ -0,0 +1,184 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::group::{Point, Scalar};
+use crate::hash::hash32;
+use crate::rng;
+
+// 鍵の導出に使う生成元 (支払い認証用, ヌリファイア用)
+fn generators() -> &'static (Point, Point) {
+    static GENERATORS: OnceLock<(Point, Point)> = OnceLock::new();
+    GENERATORS.get_or_init(|| {
+        (
+            Point::hash_to_point(b"MASPsim_KeyGen", b"SpendAuth"),
+            Point::hash_to_point(b"MASPsim_KeyGen", b"Nullifier"),
+        )
+    })
+}
+
+// 支払い認証署名の生成元
+pub fn spend_auth_base() -> &'static Point {
+    &generators().0
+}
+
+// 支払い鍵。ノートを消費する権限のもとになる秘密で、他のすべての鍵はここから導出される
+#[derive(Clone)]
+pub struct SpendingKey([u8; 32]);
+
+impl SpendingKey {
+    // シードから決定的に導出
+    pub fn from_seed(seed: &[u8]) -> Self {
+        SpendingKey(hash32(b"MASPsim_Seed", &[seed]))
+    }
+
+    // ランダムなシードから導出
+    pub fn random() -> Self {
+        let mut seed = [0u8; 32];
+        rng::fill_bytes(&mut seed);
+        SpendingKey::from_seed(&seed)
+    }
+
+    // 支払い認証鍵 ask とヌリファイア秘密鍵 nsk に展開
+    pub fn expand(&self) -> ExpandedSpendingKey {
+        ExpandedSpendingKey {
+            ask: Scalar::hash(b"MASPsim_ExpandSK", &[&self.0, &[0]]),
+            nsk: Scalar::hash(b"MASPsim_ExpandSK", &[&self.0, &[1]]),
+        }
+    }
+
+    pub fn full_viewing_key(&self) -> FullViewingKey {
+        self.expand().full_viewing_key()
+    }
+}
+
+impl fmt::Debug for SpendingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SpendingKey(..)")
+    }
+}
+
+// 展開された支払い鍵
+#[derive(Clone)]
+pub struct ExpandedSpendingKey {
+    pub ask: Scalar,
+    pub nsk: Scalar,
+}
+
+impl ExpandedSpendingKey {
+    // 入力ごとのランダムネス alpha で再ランダム化した署名鍵 rsk = ask + alpha
+    pub fn randomized_ask(&self, alpha: &Scalar) -> Scalar {
+        self.ask + *alpha
+    }
+
+    // 秘密のスカラーを公開鍵に変換して完全閲覧鍵を得る
+    pub fn full_viewing_key(&self) -> FullViewingKey {
+        let (spend_auth, nullifier) = generators();
+        FullViewingKey {
+            ak: *spend_auth * self.ask,
+            nk: NullifierKey(*nullifier * self.nsk),
+        }
+    }
+}
+
+// ヌリファイア導出鍵
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NullifierKey(pub Point);
+
+// 完全閲覧鍵。受信したノートと、それがいつ消費されたかを知ることができるが、消費はできない
+#[derive(Clone, Debug)]
+pub struct FullViewingKey {
+    pub ak: Point,
+    pub nk: NullifierKey,
+}
+
+impl FullViewingKey {
+    // 再ランダム化した検証鍵 rk = ak + [alpha] G。同じ鍵による入力どうしを関連付けられないようにする
+    pub fn randomized_ak(&self, alpha: &Scalar) -> Point {
+        self.ak + *spend_auth_base() * *alpha
+    }
+
+    pub fn incoming_viewing_key(&self) -> IncomingViewingKey {
+        IncomingViewingKey(Scalar::hash(
+            b"MASPsim_ivk",
+            &[&self.ak.to_bytes(), &self.nk.0.to_bytes()],
+        ))
+    }
+}
+
+// 受信閲覧鍵。自分宛てのノートを見つけることだけができる
+#[derive(Clone, Copy, Debug)]
+pub struct IncomingViewingKey(Scalar);
+
+impl IncomingViewingKey {
+    // ダイバーシファイアごとに異なる支払いアドレスを導出
+    pub fn address(&self, diversifier: Diversifier) -> PaymentAddress {
+        PaymentAddress {
+            diversifier,
+            pk_d: diversifier.g_d() * self.0,
+        }
+    }
+
+    // 送信者の一時公開鍵 epk との鍵共有。送信者側の [esk] pk_d と一致する
+    pub fn agree(&self, epk: &Point) -> Point {
+        *epk * self.0
+    }
+
+    // 支払いアドレスがこの鍵から導出されたものかどうか
+    pub fn owns(&self, address: &PaymentAddress) -> bool {
+        address.diversifier.g_d() * self.0 == address.pk_d
+    }
+}
+
+// 同じ鍵から互いに関連付けられない複数のアドレスを作るための値
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Diversifier(pub [u8; 11]);
+
+impl Diversifier {
+    // 番号 index のダイバーシファイア
+    pub fn from_index(index: u64) -> Self {
+        let mut bytes = [0u8; 11];
+        bytes[..8].copy_from_slice(&index.to_le_bytes());
+        Diversifier(bytes)
+    }
+
+    // ダイバーシファイアに対応する基点 g_d
+    pub fn g_d(&self) -> Point {
+        Point::hash_to_point(b"MASPsim_gd", &self.0)
+    }
+}
+
+impl fmt::Debug for Diversifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+// 支払いアドレス (d, pk_d)。pk_d = [ivk] g_d
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PaymentAddress {
+    pub diversifier: Diversifier,
+    pub pk_d: Point,
+}
+
+impl PaymentAddress {
+    // 43バイトの表現
+    pub fn to_bytes(self) -> [u8; 43] {
+        let mut bytes = [0u8; 43];
+        bytes[..11].copy_from_slice(&self.diversifier.0);
+        bytes[11..].copy_from_slice(&self.pk_d.to_bytes());
+        bytes
+    }
+}
+
+impl fmt::Debug for PaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PaymentAddress(")?;
+        for byte in self.to_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::asset::{AssetRegistry, AssetType};
+use crate::commitment::value_randomness_base;
+use crate::error::ValidationError;
+use crate::fee::FeeRule;
+use crate::issuance::issuance_base;
+use crate::keys::{spend_auth_base, FullViewingKey};
+use crate::note_encryption::EncryptedNote;
+use crate::nullifier::{Nullifier, NullifierSet};
+use crate::proof::{MockProver, ProofSystem};
+use crate::transaction::Transaction;
+use crate::transparent::{transparent_base, TransparentAddress, TransparentLedger};
+use crate::tree::{CommitmentTree, TREE_DEPTH};
+
+// 台帳のスナップショット。証明系を除いた、台帳を復元するのに必要な公開された状態。
+// コミットメントツリーは暗号化ノートのコミットメントを順に追記し直して復元する
+#[derive(Debug, Clone)]
+pub struct LedgerSnapshot {
+    pub registry: AssetRegistry,
+    pub outputs: Vec<EncryptedNote>,
+    pub nullifiers: NullifierSet,
+    pub transparent: TransparentLedger,
+    pub supply: BTreeMap<AssetType, u64>,
+    pub fee_rule: Option<FeeRule>,
+    pub unclaimed_fees: u64,
+}
+
+// 台帳の状態。資産の登録、証明系、コミットメントツリー、ヌリファイアの集合、公開された暗号化ノート、
+// 透明なアカウント、資産タイプごとの総供給量、手数料の規則と、まだブロックを作った人に渡していない手数料を保持。
+// 誰がどのノートを持っているかは知らず、それはウォレットが鍵で復号して見つける
+#[derive(Debug, Clone)]
+pub struct Ledger<P: ProofSystem = MockProver> {
+    registry: AssetRegistry,
+    proof_system: P,
+    tree: CommitmentTree,
+    nullifiers: NullifierSet,
+    outputs: Vec<EncryptedNote>, // ツリー上の位置の順に並んだ暗号化ノート
+    transparent: TransparentLedger,
+    supply: BTreeMap<AssetType, u64>, // 資産タイプごとの総供給量
+    fee_rule: Option<FeeRule>,        // なければ手数料を受け付けない
+    unclaimed_fees: u64,              // 払われたが、まだ透明なアカウントへ入金していない手数料
+}
+
+impl<P: ProofSystem> Ledger<P> {
+    // 資産の登録と、証明の検証に使う証明系を指定して空の台帳を作成
+    pub fn new(registry: AssetRegistry, proof_system: P) -> Self {
+        Ledger {
+            registry,
+            proof_system,
+            tree: CommitmentTree::new(),
+            nullifiers: NullifierSet::new(),
+            outputs: Vec::new(),
+            transparent: TransparentLedger::new(),
+            supply: BTreeMap::new(),
+            fee_rule: None,
+            unclaimed_fees: 0,
+        }
+    }
+
+    // 手数料の資産タイプと最低額の規則を指定
+    pub fn with_fee_rule(mut self, fee_rule: FeeRule) -> Self {
+        self.fee_rule = Some(fee_rule);
+        self
+    }
+
+    // スナップショットと証明系から台帳を復元する。トランザクションの区切りは残っていないので、
+    // アンカーとして受け付けるのは空のツリーと最終状態のルートだけになる
+    pub fn restore(snapshot: LedgerSnapshot, proof_system: P) -> Self {
+        let mut tree = CommitmentTree::new();
+        for output in &snapshot.outputs {
+            tree.append(output.cm);
+        }
+        tree.checkpoint();
+        Ledger {
+            registry: snapshot.registry,
+            proof_system,
+            tree,
+            nullifiers: snapshot.nullifiers,
+            outputs: snapshot.outputs,
+            transparent: snapshot.transparent,
+            supply: snapshot.supply,
+            fee_rule: snapshot.fee_rule,
+            unclaimed_fees: snapshot.unclaimed_fees,
+        }
+    }
+
+    // 現在の状態のスナップショット
+    pub fn snapshot(&self) -> LedgerSnapshot {
+        LedgerSnapshot {
+            registry: self.registry.clone(),
+            outputs: self.outputs.clone(),
+            nullifiers: self.nullifiers.clone(),
+            transparent: self.transparent.clone(),
+            supply: self.supply.clone(),
+            fee_rule: self.fee_rule,
+            unclaimed_fees: self.unclaimed_fees,
+        }
+    }
+
+    pub fn registry(&self) -> &AssetRegistry {
+        &self.registry
+    }
+
+    pub fn proof_system(&self) -> &P {
+        &self.proof_system
+    }
+
+    pub fn tree(&self) -> &CommitmentTree {
+        &self.tree
+    }
+
+    pub fn nullifiers(&self) -> &NullifierSet {
+        &self.nullifiers
+    }
+
+    pub fn outputs(&self) -> &[EncryptedNote] {
+        &self.outputs
+    }
+
+    pub fn transparent(&self) -> &TransparentLedger {
+        &self.transparent
+    }
+
+    // 資産タイプの総供給量
+    pub fn supply(&self, asset_type: &AssetType) -> u64 {
+        self.supply.get(asset_type).copied().unwrap_or(0)
+    }
+
+    pub fn fee_rule(&self) -> Option<&FeeRule> {
+        self.fee_rule.as_ref()
+    }
+
+    fn fee_asset(&self) -> Option<&AssetType> {
+        self.fee_rule.as_ref().map(|fee_rule| &fee_rule.asset_type)
+    }
+
+    // 払われたが、まだ透明なアカウントへ入金していない手数料
+    pub fn unclaimed_fees(&self) -> u64 {
+        self.unclaimed_fees
+    }
+
+    // トランザクションを検証する。台帳の状態は変更しない。ノートの内容は見ずに、証明と署名だけを確認する
+    pub fn validate(&self, transaction: &Transaction) -> Result<(), ValidationError> {
+        // 登録されていない資産タイプをミントしたり、手数料や透明な出力として支払うことはできない
+        if let Some(asset_type) = transaction
+            .value_balance
+            .keys()
+            .chain(
+                transaction
+                    .transparent_outputs
+                    .iter()
+                    .map(|output| &output.asset_type),
+            )
+            .find(|asset_type| !self.registry.contains(asset_type))
+        {
+            return Err(ValidationError::UnknownAssetType(*asset_type));
+        }
+        if !self.tree.is_known_anchor(&transaction.anchor) {
+            return Err(ValidationError::BadAnchor(transaction.anchor));
+        }
+
+        // 入力ノートがアンカーのツリーに含まれ、ヌリファイア、値コミットメント、rk が正しく導出されていることを証明で確認し、
+        // rk による署名で所有者に認可されていて、ヌリファイアがまだ公開されていないことを確認する
+        let sighash = transaction.sighash();
+        let mut revealed = HashSet::new();
+        for (index, input) in transaction.inputs.iter().enumerate() {
+            if !self
+                .proof_system
+                .verify(&transaction.spend_statement(index), &input.proof)
+            {
+                return Err(ValidationError::InvalidSpendProof(input.nullifier));
+            }
+            if !input
+                .spend_auth_sig
+                .verify(spend_auth_base(), &input.rk, &sighash)
+            {
+                return Err(ValidationError::BadSignature(input.nullifier));
+            }
+            if self.nullifiers.contains(&input.nullifier) || !revealed.insert(input.nullifier) {
+                return Err(ValidationError::DoubleSpend(input.nullifier));
+            }
+        }
+
+        // 出力のノートコミットメントと値コミットメントが同じノートから導出されていることを証明で確認する
+        for (index, output) in transaction.outputs.iter().enumerate() {
+            if !self
+                .proof_system
+                .verify(&transaction.output_statement(index), &output.proof)
+            {
+                return Err(ValidationError::InvalidOutputProof(output.encrypted.cm));
+            }
+        }
+
+        // 透明な入力がアカウントの鍵で署名され、現在のノンスを使っていて、残高が足りることを確認する
+        for input in &transaction.transparent_inputs {
+            if TransparentAddress::from_public_key(&input.public_key) != input.address
+                || !input
+                    .signature
+                    .verify(transparent_base(), &input.public_key, &sighash)
+            {
+                return Err(ValidationError::BadTransparentSignature(input.address));
+            }
+            if input.nonce != self.transparent.nonce(&input.address) {
+                return Err(ValidationError::StaleNonce(input.address));
+            }
+        }
+        self.transparent_balances(transaction)?;
+
+        // ミントは資産の登録に紐付いた発行者の鍵で署名されている必要がある
+        for issuance in &transaction.issuance {
+            if self.registry.issuer(&issuance.asset_type) != Some(&issuance.issuer)
+                || !issuance
+                    .signature
+                    .verify(issuance_base(), &issuance.issuer, &sighash)
+            {
+                return Err(ValidationError::UnauthorizedIssuance(issuance.asset_type));
+            }
+        }
+        // 手数料は規則の最低額以上でなければならず、資産タイプごとに入ってくる量と出ていく量が一致する必要がある
+        match &self.fee_rule {
+            Some(fee_rule) => {
+                let required = fee_rule.minimum_for(transaction);
+                if transaction.fee < required {
+                    return Err(ValidationError::InsufficientFee {
+                        required,
+                        paid: transaction.fee,
+                    });
+                }
+            }
+            None if transaction.fee > 0 => return Err(ValidationError::NoFeeAsset),
+            None => {}
+        }
+        if let Some(asset_type) = transaction.unbalanced_asset(self.fee_asset()) {
+            return Err(ValidationError::ValueImbalance(asset_type));
+        }
+        self.next_supply(transaction)?;
+
+        // 資産タイプごとのシールドされた側の収支は、量を見ずにバインディング署名で確認する。
+        // 入力も出力もなければ検証鍵が単位元になり署名で確かめられないので、公開された収支が0であることを直接確認する
+        let balanced = if transaction.inputs.is_empty() && transaction.outputs.is_empty() {
+            transaction.value_balance.values().all(|value| *value == 0)
+        } else {
+            transaction.binding_sig.verify(
+                value_randomness_base(),
+                &transaction.binding_verification_key(),
+                &sighash,
+            )
+        };
+        if !balanced {
+            return Err(ValidationError::BadBindingSignature);
+        }
+
+        // 出力をすべて追記できるだけの空きがツリーにあることを確認する
+        if self.tree.size() + transaction.outputs.len() as u64 > 1 << TREE_DEPTH {
+            return Err(ValidationError::TreeFull);
+        }
+        Ok(())
+    }
+
+    // 透明な入力と出力を反映したあとの残高を、アドレスと資産タイプごとに計算する
+    fn transparent_balances(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<BTreeMap<(TransparentAddress, AssetType), u64>, ValidationError> {
+        let mut balances = BTreeMap::new();
+        for input in &transaction.transparent_inputs {
+            let key = (input.address, input.asset_type);
+            let balance = balances
+                .entry(key)
+                .or_insert_with(|| self.transparent.balance(&input.address, &input.asset_type));
+            *balance = balance.checked_sub(input.amount).ok_or(
+                ValidationError::InsufficientTransparentBalance(input.address, input.asset_type),
+            )?;
+        }
+        for output in &transaction.transparent_outputs {
+            let key = (output.address, output.asset_type);
+            let balance = balances.entry(key).or_insert_with(|| {
+                self.transparent
+                    .balance(&output.address, &output.asset_type)
+            });
+            *balance = balance.checked_add(output.amount).ok_or(
+                ValidationError::TransparentBalanceOverflow(output.address, output.asset_type),
+            )?;
+        }
+        Ok(balances)
+    }
+
+    // ミントで増え、バーンで減ったあとの総供給量を、ミントとバーンの資産タイプごとに計算する。
+    // 手数料はブロックを作った人へ渡るので総供給量は変わらない
+    fn next_supply(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<BTreeMap<AssetType, u64>, ValidationError> {
+        let mut supply: BTreeMap<AssetType, i128> = BTreeMap::new();
+        for issuance in &transaction.issuance {
+            *supply
+                .entry(issuance.asset_type)
+                .or_insert_with(|| i128::from(self.supply(&issuance.asset_type))) +=
+                i128::from(issuance.amount);
+        }
+        for (asset_type, amount) in &transaction.burns {
+            *supply
+                .entry(*asset_type)
+                .or_insert_with(|| i128::from(self.supply(asset_type))) -= i128::from(*amount);
+        }
+        supply
+            .into_iter()
+            .map(|(asset_type, next)| {
+                if next < 0 {
+                    return Err(ValidationError::BurnExceedsSupply(asset_type));
+                }
+                u64::try_from(next)
+                    .map(|next| (asset_type, next))
+                    .map_err(|_| ValidationError::SupplyOverflow(asset_type))
+            })
+            .collect()
+    }
+
+    // 総供給量が、未消費のノートと透明なアカウントの残高と未入金の手数料の合計と一致するかを確認し、一致しない資産タイプを返す。
+    // ノートの量は、渡された完全閲覧鍵で出力を復号し、ヌリファイアが公開されていないものを数える
+    pub fn supply_mismatch(&self, viewing_keys: &[FullViewingKey]) -> Option<AssetType> {
+        let mut held: BTreeMap<AssetType, u128> = BTreeMap::new();
+        if let Some(fee_asset) = self.fee_asset() {
+            *held.entry(*fee_asset).or_default() += u128::from(self.unclaimed_fees);
+        }
+        for (_, account) in self.transparent.accounts() {
+            for (asset_type, balance) in &account.balances {
+                *held.entry(*asset_type).or_default() += u128::from(*balance);
+            }
+        }
+        for (position, output) in self.outputs.iter().enumerate() {
+            let Some((fvk, note)) = viewing_keys.iter().find_map(|fvk| {
+                output
+                    .try_decrypt(&fvk.incoming_viewing_key())
+                    .map(|note| (fvk, note))
+            }) else {
+                continue;
+            };
+            let nullifier = Nullifier::derive(&fvk.nk, &note.commit(), position as u64);
+            if !self.nullifiers.contains(&nullifier) {
+                *held.entry(note.asset_type).or_default() += u128::from(note.amount);
+            }
+        }
+        self.supply
+            .keys()
+            .chain(held.keys())
+            .find(|asset_type| {
+                u128::from(self.supply(asset_type)) != held.get(asset_type).copied().unwrap_or(0)
+            })
+            .copied()
+    }
+
+    // トランザクションをすべて検証してから、その変更をまとめて反映する。
+    // 検証に失敗した場合は台帳の状態を一切変更せずに理由を返す
+    pub fn apply(&mut self, transaction: &Transaction) -> Result<(), ValidationError> {
+        self.validate(transaction)?;
+
+        // 失敗しうる計算をすべて先に済ませ、台帳を書き換え始めてからは失敗しないようにする
+        let supply = self.next_supply(transaction)?;
+        let balances = self.transparent_balances(transaction)?;
+        let unclaimed_fees = self
+            .unclaimed_fees
+            .checked_add(transaction.fee)
+            .ok_or(ValidationError::FeeOverflow)?;
+
+        // ヌリファイアを記録して入力ノートを消費済みにする
+        for input in &transaction.inputs {
+            self.nullifiers.insert(input.nullifier);
+        }
+
+        // 総供給量を更新する
+        self.supply.extend(supply);
+
+        // 手数料はブロックを作った人が受け取るまで台帳が預かる
+        self.unclaimed_fees = unclaimed_fees;
+
+        // 透明なアカウントの残高を更新し、入力を使ったアカウントのノンスを進める
+        for ((address, asset_type), balance) in balances {
+            self.transparent.set_balance(address, asset_type, balance);
+        }
+        let spenders: HashSet<TransparentAddress> = transaction
+            .transparent_inputs
+            .iter()
+            .map(|input| input.address)
+            .collect();
+        for address in spenders {
+            self.transparent.increment_nonce(address);
+        }
+
+        // 出力ノートのコミットメントをツリーに追記し、暗号化されたノートを公開する。受取人は試しに復号して見つける
+        for output in &transaction.outputs {
+            self.tree.append(output.encrypted.cm);
+            self.outputs.push(output.encrypted.clone());
+        }
+        self.tree.checkpoint();
+        Ok(())
+    }
+
+    // 預かっている手数料を、手数料の資産タイプで address の透明なアカウントへ入金し、その量を返す
+    pub fn collect_fees(&mut self, address: TransparentAddress) -> Result<u64, ValidationError> {
+        let fees = self.unclaimed_fees;
+        let Some(fee_asset) = self.fee_asset().copied() else {
+            return Ok(0);
+        };
+        if fees == 0 {
+            return Ok(0);
+        }
+        let balance = self
+            .transparent
+            .balance(&address, &fee_asset)
+            .checked_add(fees)
+            .ok_or(ValidationError::TransparentBalanceOverflow(
+                address, fee_asset,
+            ))?;
+        self.transparent.set_balance(address, fee_asset, balance);
+        self.unclaimed_fees = 0;
+        Ok(fees)
+    }
+}
@@ -0,0 +1,30 @@
+// MASP (Multi-Asset Shielded Pool) のシミュレーター
+mod aead;
+pub mod asset;
+pub mod block;
+pub mod builder;
+pub mod chain;
+pub mod commitment;
+pub mod encoding;
+pub mod error;
+pub mod fee;
+pub mod group;
+mod hash;
+pub mod issuance;
+pub mod keys;
+pub mod ledger;
+pub mod mempool;
+pub mod note;
+pub mod note_encryption;
+pub mod nullifier;
+pub mod proof;
+// 乱数は /dev/urandom から読むので、Unix 系以外の環境ではビルドしない
+#[cfg(not(unix))]
+compile_error!("masp_simulation reads randomness from /dev/urandom and supports only unix targets");
+#[cfg(unix)]
+mod rng;
+pub mod signature;
+pub mod transaction;
+pub mod transparent;
+pub mod tree;
+pub mod wallet;
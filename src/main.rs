@@ -1,148 +1,286 @@
-use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-
-// ノート構造体の定義。資産のタイプと量を保持
-#[derive(Debug, Clone, Hash)]
-struct Note {
-    asset_type: String,
-    amount: u64,
-}
+use masp_simulation::asset::AssetRegistry;
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::chain::Chain;
+use masp_simulation::fee::FeeRule;
+use masp_simulation::issuance::IssuanceKey;
+use masp_simulation::ledger::Ledger;
+use masp_simulation::note::Note;
+use masp_simulation::proof::MockProver;
+use masp_simulation::transaction::UnauthorizedTransaction;
+use masp_simulation::transparent::TransparentKey;
+use masp_simulation::wallet::{NoteFilter, NoteStatus, Wallet};
 
-impl Note {
-    // ノートのハッシュ値を計算して、簡易的なコミットメントを生成
-    fn commit(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.hash(&mut hasher);
-        hasher.finish()
+// ウォレットをチェーンの先頭のブロックまで同期する
+fn sync(chain: &Chain, wallets: [&mut Wallet; 3]) {
+    for wallet in wallets {
+        for block in &chain.blocks()[wallet.height() as usize..] {
+            wallet.sync(block).expect("blocks on the chain are valid");
+        }
     }
 }
 
-// トランザクション構造体の定義。
-#[derive(Debug)]
-struct Transaction {
-    from_commit: u64, // 送信元ノートのコミットメント
-    to: String,       // 受取人のユーザーID
-    to_commit: u64,   // 受取人のノートコミットメント
-}
+fn main() {
+    // 資産と発行者の鍵の登録。量は最小単位 (1 BTC = 10^8) で扱う
+    let mut registry = AssetRegistry::new();
+    registry.register("BTC", 8, None);
+    let btc = registry.lookup("BTC", 8, None).expect("BTC is registered");
+    let issuer_key = IssuanceKey::random();
+    registry.set_issuer(&btc, issuer_key.public_key());
+    let coin = 100_000_000;
 
-impl Transaction {
-    // トランザクションの新規作成。送信元と受取人のノートからコミットメントを計算します。
-    fn new(from: &Note, to: &str, to_note: &Note) -> Self {
-        Transaction {
-            from_commit: from.commit(),
-            to: to.to_string(),
-            to_commit: to_note.commit(),
-        }
+    // 手数料はBTCで払い、1トランザクションあたり1000、入力と出力1つあたり500を最低額とする。
+    // トランザクションはブロックにまとめてチェーンに追加し、手数料はブロックを作った人の透明なアカウントへ入る
+    let fee_rule = FeeRule::new(btc, 1000, 500);
+    let mut chain = Chain::new(Ledger::new(registry, MockProver::setup()).with_fee_rule(fee_rule));
+    let producer = TransparentKey::random().address();
+
+    // ユーザーごとのウォレット。台帳は誰がどのノートを持つかを知らず、各ウォレットがブロックを走査して見つける
+    let mut issuer = Wallet::new("Issuer");
+    let mut alice = Wallet::new("Alice");
+    let mut bob = Wallet::new("Bob");
+
+    // 発行者が111 BTCをミントし、Aliceのアドレス宛てに60 BTCと40 BTCのノートを、Bobの透明なアカウントに10 BTCを渡す。
+    // 残りから手数料を払い、お釣りは発行者のノートになる
+    let issuance = TransactionBuilder::new(&issuer)
+        .mint(&issuer_key, btc, 111 * coin)
+        .add_output(alice.address(), btc, 60 * coin)
+        .add_output(alice.address(), btc, 40 * coin)
+        .add_transparent_output(bob.transparent_address(), btc, 10 * coin)
+        .with_fee_rule(fee_rule)
+        .build(chain.ledger().proof_system())
+        .expect("minted value covers the outputs");
+    match chain.produce_block(vec![issuance], producer) {
+        Ok(block) => println!(
+            "Issuance transaction verified and completed in block {}",
+            block.header.height
+        ),
+        Err(err) => println!("Issuance transaction verification failed: {}", err),
     }
-}
 
-// ユーザー構造体の定義。ユーザーIDと所有するノートのリストを保持
-#[derive(Debug)]
-struct User {
-    id: String,
-    notes: Vec<Note>,
-}
+    // 発行者として登録されていない鍵ではミントできない
+    let forged = TransactionBuilder::new(&bob)
+        .mint(&IssuanceKey::random(), btc, 1001 * coin)
+        .add_output(bob.address(), btc, 1000 * coin)
+        .with_fee_rule(fee_rule)
+        .build(chain.ledger().proof_system())
+        .expect("minted value covers the outputs");
+    if let Err(err) = chain.produce_block(vec![forged], producer) {
+        println!("Unauthorized issuance rejected: {}", err);
+    }
 
-impl User {
-    // ユーザーの新規作成
-    fn new(id: &str) -> Self {
-        User {
-            id: id.to_string(),
-            notes: Vec::new(),
+    // 60 BTCと40 BTCのノートを統合し、100 BTCのノートにする
+    sync(&chain, [&mut issuer, &mut alice, &mut bob]);
+    if let Some(merge) = alice
+        .merge_notes(
+            btc,
+            chain.ledger().fee_rule(),
+            chain.ledger().proof_system(),
+        )
+        .expect("Alice's BTC notes can be merged")
+    {
+        match chain.produce_block(vec![merge], producer) {
+            Ok(block) => println!(
+                "Merge transaction verified and completed in block {}",
+                block.header.height
+            ),
+            Err(err) => println!("Merge transaction verification failed: {}", err),
         }
     }
+    sync(&chain, [&mut issuer, &mut alice, &mut bob]);
 
-    // ノートの追加
-    fn add_note(&mut self, note: Note) {
-        self.notes.push(note);
+    // トランザクションの作成と実行。Aliceのノートから50 BTCをBobへ送り、手数料1 BTCを除いた残りはお釣りとしてAliceへ戻す
+    let transaction = TransactionBuilder::new(&alice)
+        .add_output(bob.address(), btc, 50 * coin)
+        .with_fee_rule(fee_rule)
+        .with_fee(coin)
+        .build(chain.ledger().proof_system())
+        .expect("Alice has enough BTC");
+
+    // ブロックに含まれるまで、消費するノートは使えず、お釣りは未確定の残高になる
+    alice.mark_pending(&transaction);
+    let balance = alice
+        .balance(&btc)
+        .expect("Alice's balance fits in an amount");
+    println!(
+        "Alice before confirmation: {} spendable, {} pending",
+        chain
+            .ledger()
+            .registry()
+            .format_amount(&btc, balance.spendable),
+        chain
+            .ledger()
+            .registry()
+            .format_amount(&btc, balance.pending)
+    );
+
+    // トランザクションを検証して、適切にノートを移動
+    match chain.produce_block(vec![transaction.clone()], producer) {
+        Ok(block) => println!(
+            "Transaction {:?} verified and completed in block {}",
+            transaction.txid(),
+            block.header.height
+        ),
+        Err(err) => println!("Transaction verification failed: {}", err),
     }
 
-    // 指定されたコミットメントに一致するノートの削除
-    fn remove_note(&mut self, commit: u64) -> Option<Note> {
-        if let Some(pos) = self.notes.iter().position(|x| x.commit() == commit) {
-            Some(self.notes.remove(pos))
-        } else {
-            None
-        }
+    // 同じノートを再び消費しようとするトランザクションは拒否される
+    if let Err(err) = chain.produce_block(vec![transaction], producer) {
+        println!("Double spend rejected: {}", err);
     }
+    sync(&chain, [&mut issuer, &mut alice, &mut bob]);
 
-    // 特定の資産タイプのノートを統合し、新しいノートを作成
-    fn merge_notes(&mut self, asset_type: &str) {
-        let mut amount = 0;
-        self.notes.retain(|note| {
-            if note.asset_type == asset_type {
-                amount += note.amount;
-                false
-            } else {
-                true
-            }
-        });
-        if amount > 0 {
-            self.add_note(Note {
-                asset_type: asset_type.to_string(),
-                amount,
-            });
-        }
+    // Bobは透明なアカウントの10 BTCをシールドしてノートにする
+    let nonce = chain
+        .ledger()
+        .transparent()
+        .nonce(&bob.transparent_address());
+    let shielding = TransactionBuilder::new(&bob)
+        .add_transparent_input(btc, 10 * coin, nonce)
+        .with_fee_rule(fee_rule)
+        .build(chain.ledger().proof_system())
+        .expect("Bob has a transparent balance");
+    match chain.produce_block(vec![shielding.clone()], producer) {
+        Ok(block) => println!(
+            "Shielding transaction verified and completed in block {}",
+            block.header.height
+        ),
+        Err(err) => println!("Shielding transaction verification failed: {}", err),
+    }
+    if let Err(err) = chain.produce_block(vec![shielding], producer) {
+        println!("Replayed shielding rejected: {}", err);
     }
-}
 
-// トランザクションの検証関数。正当なトランザクションであるかを検証し、対応する処理を実行
-fn verify_transaction(users: &mut HashMap<String, User>, transaction: &Transaction) -> bool {
-    if let Some(user) = users.get_mut(&transaction.to) {
-        user.add_note(Note {
-            asset_type: "BTC".to_string(), // これはトランザクションの内容から導出する必要がある
-            amount: 50,                    // これもトランザクションの内容から導出する必要がある
-        });
-        if let Some(user) = users.get_mut("Alice") {
-            user.remove_note(transaction.from_commit);
-            user.add_note(Note {
-                asset_type: "BTC".to_string(), // これはトランザクションの内容から導出する必要がある
-                amount: 50,                    // これもトランザクションの内容から導出する必要がある
-            });
-            true
-        } else {
-            println!(
-                "Transaction verification failed with user {}",
-                transaction.to
-            );
-            false
-        }
-    } else {
-        println!("Transaction verification failed with no user");
-        false
+    // Aliceはノートから20 BTCを自分の透明なアカウントへ引き出す
+    sync(&chain, [&mut issuer, &mut alice, &mut bob]);
+    let unshielding = TransactionBuilder::new(&alice)
+        .add_transparent_output(alice.transparent_address(), btc, 20 * coin)
+        .with_fee_rule(fee_rule)
+        .build(chain.ledger().proof_system())
+        .expect("Alice has enough BTC");
+    match chain.produce_block(vec![unshielding], producer) {
+        Ok(block) => println!(
+            "Unshielding transaction verified and completed in block {}",
+            block.header.height
+        ),
+        Err(err) => println!("Unshielding transaction verification failed: {}", err),
     }
-}
+    sync(&chain, [&mut issuer, &mut alice, &mut bob]);
 
-fn main() {
-    // ユーザーとノートの初期設定
-    let mut users = HashMap::new();
-    let mut alice = User::new("Alice");
-    let mut bob = User::new("Bob");
-
-    alice.add_note(Note {
-        asset_type: "BTC".to_string(),
-        amount: 100,
-    });
-
-    let note_to_bob = Note {
-        asset_type: "BTC".to_string(),
-        amount: 50,
-    };
-    // ユーザーの情報をHashMapに追加
-    users.insert("Alice".to_string(), alice);
-    users.insert("Bob".to_string(), bob);
-
-    // トランザクションの作成と実行
-    let transaction = Transaction::new(&users["Alice"].notes[0], "Bob", &note_to_bob);
+    // 残高を超える送金は組み立ての段階で失敗する
+    if let Err(err) = TransactionBuilder::new(&bob)
+        .add_output(bob.address(), btc, 80 * coin)
+        .with_fee_rule(fee_rule)
+        .build(chain.ledger().proof_system())
+    {
+        println!("Overspending transaction not built: {}", err);
+    }
 
-    // トランザクションを検証して、適切にノートを移動
-    if verify_transaction(&mut users, &transaction) {
-        println!("Transaction verified and completed");
-    } else {
-        println!("Transaction verification failed");
+    // 入力より多くの価値を作り出すトランザクションは、手数料を払っていても拒否され、台帳の状態は変わらない
+    let root_before = chain.ledger().tree().root();
+    let fee = fee_rule.minimum(2);
+    let spent = bob.unspent_notes().next().expect("Bob has a note");
+    let mut inflation = UnauthorizedTransaction::new(
+        bob.anchor(),
+        vec![bob.spend(spent)],
+        vec![Note::new(btc, 100 * coin, bob.address())],
+    )
+    .with_value_balance(btc, fee as i64)
+    .with_fee(fee);
+    bob.authorize(&mut inflation);
+    let inflation = inflation
+        .prove(chain.ledger().proof_system())
+        .expect("each note is valid on its own");
+    if let Err(err) = chain.produce_block(vec![inflation], producer) {
+        println!("Inflating transaction rejected: {}", err);
+    }
+
+    // Aliceのノートを自分宛てに送ろうとしても、BobはAliceの鍵で署名できないので拒否される
+    let stolen = alice.unspent_notes().next().expect("Alice has a note");
+    let mut theft = UnauthorizedTransaction::new(
+        alice.anchor(),
+        vec![alice.spend(stolen)],
+        vec![Note::new(btc, stolen.note.amount, bob.address())],
+    );
+    bob.authorize(&mut theft);
+    let theft = theft
+        .prove(chain.ledger().proof_system())
+        .expect("the note itself is valid");
+    if let Err(err) = chain.produce_block(vec![theft], producer) {
+        println!("Unauthorized spend rejected: {}", err);
+    }
+    assert_eq!(chain.ledger().tree().root(), root_before);
+
+    // Bobは5 BTCをバーンする
+    let burn = TransactionBuilder::new(&bob)
+        .burn(btc, 5 * coin)
+        .with_fee_rule(fee_rule)
+        .build(chain.ledger().proof_system())
+        .expect("Bob has enough BTC");
+    match chain.produce_block(vec![burn], producer) {
+        Ok(block) => println!(
+            "Burn transaction verified and completed in block {}",
+            block.header.height
+        ),
+        Err(err) => println!("Burn transaction verification failed: {}", err),
     }
+    sync(&chain, [&mut issuer, &mut alice, &mut bob]);
 
-    // トランザクション後のユーザー情報を表示
-    println!("Users after transaction: {:?}", users);
+    // トランザクション後のウォレットの情報を表示
+    let ledger = chain.ledger();
+    let wallets = [&issuer, &alice, &bob];
+    for wallet in wallets {
+        let spendable = NoteFilter::new().with_status(NoteStatus::Spendable);
+        for received in wallet.list_notes(&spendable) {
+            println!(
+                "{}: {} at position {}",
+                wallet.id,
+                ledger
+                    .registry()
+                    .format_amount(&received.note.asset_type, received.note.amount),
+                received.position
+            );
+        }
+        let balances = wallet.balances().expect("wallet balances fit in an amount");
+        for (asset_type, balance) in balances {
+            println!(
+                "{}: {} in total",
+                wallet.id,
+                ledger.registry().format_amount(
+                    &asset_type,
+                    balance.total().expect("balance fits in an amount")
+                )
+            );
+        }
+        let balance = ledger
+            .transparent()
+            .balance(&wallet.transparent_address(), &btc);
+        if balance > 0 {
+            println!(
+                "{}: {} in transparent account",
+                wallet.id,
+                ledger.registry().format_amount(&btc, balance)
+            );
+        }
+    }
+    println!(
+        "Block producer collected {} in fees",
+        ledger
+            .registry()
+            .format_amount(&btc, ledger.transparent().balance(&producer, &btc))
+    );
+    println!(
+        "Total supply: {}",
+        ledger.registry().format_amount(&btc, ledger.supply(&btc))
+    );
+    let viewing_keys = wallets.map(Wallet::full_viewing_key);
+    match ledger.supply_mismatch(&viewing_keys) {
+        None => println!("Supply matches unspent notes and transparent balances"),
+        Some(asset_type) => println!("Supply mismatch for {:?}", asset_type),
+    }
+    println!("Commitment tree root: {:?}", ledger.tree().root());
+    println!(
+        "Chain height {} with tip {:?}",
+        chain.height(),
+        chain.tip_hash()
+    );
 }
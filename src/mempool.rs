@@ -0,0 +1,144 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::block::Block;
+use crate::chain::Chain;
+use crate::error::{BlockError, MempoolError};
+use crate::nullifier::Nullifier;
+use crate::proof::ProofSystem;
+use crate::transaction::{Transaction, TxId};
+use crate::transparent::TransparentAddress;
+
+// 保留中のトランザクションと、受け付けた時点の手数料と順番
+#[derive(Debug, Clone)]
+struct PendingTransaction {
+    transaction: Transaction,
+    fee: u64,
+    sequence: u64,
+}
+
+// ブロックに含まれるのを待つトランザクションの集合。保留中のトランザクションどうしはヌリファイアが衝突しない
+#[derive(Debug, Clone, Default)]
+pub struct Mempool {
+    pending: HashMap<TxId, PendingTransaction>,
+    spent: HashMap<Nullifier, TxId>, // 保留中のトランザクションが公開するヌリファイアと、その txid
+    next_sequence: u64,
+}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Mempool::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn contains(&self, txid: &TxId) -> bool {
+        self.pending.contains_key(txid)
+    }
+
+    pub fn get(&self, txid: &TxId) -> Option<&Transaction> {
+        self.pending.get(txid).map(|pending| &pending.transaction)
+    }
+
+    // チェーンの先頭の台帳に対して検証し、保留中のトランザクションとヌリファイアが衝突しなければ受け付ける
+    pub fn insert<P: ProofSystem + Clone>(
+        &mut self,
+        chain: &Chain<P>,
+        transaction: Transaction,
+    ) -> Result<TxId, MempoolError> {
+        let txid = transaction.txid();
+        if self.contains(&txid) {
+            return Err(MempoolError::AlreadyPending(txid));
+        }
+        if let Some((nullifier, other)) = transaction.inputs.iter().find_map(|input| {
+            self.spent
+                .get(&input.nullifier)
+                .map(|other| (input.nullifier, *other))
+        }) {
+            return Err(MempoolError::Conflict(nullifier, other));
+        }
+        if !chain.is_recent_anchor(&transaction.anchor) {
+            return Err(MempoolError::StaleAnchor(transaction.anchor));
+        }
+        chain
+            .ledger()
+            .validate(&transaction)
+            .map_err(MempoolError::Invalid)?;
+
+        for input in &transaction.inputs {
+            self.spent.insert(input.nullifier, txid);
+        }
+        self.pending.insert(
+            txid,
+            PendingTransaction {
+                fee: transaction.fee,
+                transaction,
+                sequence: self.next_sequence,
+            },
+        );
+        self.next_sequence += 1;
+        Ok(txid)
+    }
+
+    // 保留中のトランザクションを取り除く
+    pub fn remove(&mut self, txid: &TxId) -> Option<Transaction> {
+        let pending = self.pending.remove(txid)?;
+        for input in &pending.transaction.inputs {
+            self.spent.remove(&input.nullifier);
+        }
+        Some(pending.transaction)
+    }
+
+    // 手数料の高い順。手数料が同じなら先に受け付けた順
+    pub fn by_fee(&self) -> Vec<&Transaction> {
+        let mut pending: Vec<&PendingTransaction> = self.pending.values().collect();
+        pending.sort_by_key(|pending| (Reverse(pending.fee), pending.sequence));
+        pending
+            .into_iter()
+            .map(|pending| &pending.transaction)
+            .collect()
+    }
+
+    // 手数料の高い順に、チェーンの先頭に適用できるトランザクションを最大 max_transactions 個含めた次のブロックを組み立てる。
+    // 手数料は fee_recipient が受け取る
+    pub fn build_block<P: ProofSystem + Clone>(
+        &self,
+        chain: &Chain<P>,
+        fee_recipient: TransparentAddress,
+        max_transactions: usize,
+    ) -> Result<Block, BlockError> {
+        chain.select_block(
+            self.by_fee().into_iter().cloned(),
+            fee_recipient,
+            max_transactions,
+        )
+    }
+
+    // チェーンに追加されたブロックに含まれたトランザクションを取り除き、
+    // 残りのうちブロックと衝突するなどして先頭の台帳に適用できなくなったものを追い出して、その txid を返す
+    pub fn prune<P: ProofSystem + Clone>(&mut self, chain: &Chain<P>, block: &Block) -> Vec<TxId> {
+        for transaction in &block.transactions {
+            self.remove(&transaction.txid());
+        }
+        let mut stale: Vec<TxId> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| {
+                !chain.is_recent_anchor(&pending.transaction.anchor)
+                    || chain.ledger().validate(&pending.transaction).is_err()
+            })
+            .map(|(txid, _)| *txid)
+            .collect();
+        stale.sort();
+        for txid in &stale {
+            self.remove(txid);
+        }
+        stale
+    }
+}
@@ -0,0 +1,30 @@
+use crate::asset::AssetType;
+use crate::commitment::{commit_note, NoteCommitment};
+use crate::group::Scalar;
+use crate::keys::PaymentAddress;
+
+// ノート構造体の定義。資産のタイプと量、受取人のアドレス、コミットメントのランダムネスを保持
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub recipient: PaymentAddress, // このノートを受け取り、消費できるアドレス
+    pub rcm: Scalar,               // コミットメントを隠すためのブラインディング係数
+}
+
+impl Note {
+    // ランダムなブラインディング係数を持つノートの新規作成
+    pub fn new(asset_type: AssetType, amount: u64, recipient: PaymentAddress) -> Self {
+        Note {
+            asset_type,
+            amount,
+            recipient,
+            rcm: Scalar::random(),
+        }
+    }
+
+    // ノートの内容とランダムネスから、秘匿性と拘束性を持つコミットメントを生成
+    pub fn commit(&self) -> NoteCommitment {
+        commit_note(&self.asset_type, self.amount, &self.recipient, &self.rcm)
+    }
+}
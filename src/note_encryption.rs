@@ -0,0 +1,73 @@
+use crate::aead;
+use crate::asset::AssetType;
+use crate::commitment::NoteCommitment;
+use crate::group::{Point, Scalar};
+use crate::hash::hash32;
+use crate::keys::{Diversifier, IncomingViewingKey};
+use crate::note::Note;
+
+// 鍵は出力ごとに使い捨てなので、ノンスは固定でよい
+const NONCE: [u8; 12] = [0; 12];
+
+// 台帳に公開される出力。ノートコミットメントと、受取人だけが復号できる暗号化されたノートを保持
+#[derive(Debug, Clone)]
+pub struct EncryptedNote {
+    pub cm: NoteCommitment,
+    pub epk: Point,          // 送信者の一時公開鍵 [esk] g_d
+    pub ciphertext: Vec<u8>, // ノートの平文を ChaCha20-Poly1305 で暗号化したもの
+}
+
+// 鍵共有の結果と一時公開鍵から対称鍵を導出
+fn kdf(shared_secret: &Point, epk: &Point) -> [u8; 32] {
+    hash32(
+        b"MASPsim_NoteKDF",
+        &[&shared_secret.to_bytes(), &epk.to_bytes()],
+    )
+}
+
+// ノートの平文の長さ
+const PLAINTEXT_LEN: usize = 11 + 8 + 32 + 32;
+
+// ノートの平文: ダイバーシファイア (11) || 量 (8) || rcm (32) || 資産タイプ (32)
+fn encode_plaintext(note: &Note) -> Vec<u8> {
+    let mut plaintext = Vec::with_capacity(PLAINTEXT_LEN);
+    plaintext.extend_from_slice(&note.recipient.diversifier.0);
+    plaintext.extend_from_slice(&note.amount.to_le_bytes());
+    plaintext.extend_from_slice(&note.rcm.to_bytes());
+    plaintext.extend_from_slice(&note.asset_type.to_bytes());
+    plaintext
+}
+
+impl EncryptedNote {
+    // 受取人のアドレスとの鍵共有で導出した鍵でノートを暗号化
+    pub fn encrypt(note: &Note) -> Self {
+        let esk = Scalar::random();
+        let epk = note.recipient.diversifier.g_d() * esk;
+        let key = kdf(&(note.recipient.pk_d * esk), &epk);
+        let cm = note.commit();
+        EncryptedNote {
+            cm,
+            epk,
+            ciphertext: aead::seal(&key, &NONCE, &cm.0, &encode_plaintext(note)),
+        }
+    }
+
+    // 受信閲覧鍵で試しに復号し、自分宛てのノートであれば取り出す
+    pub fn try_decrypt(&self, ivk: &IncomingViewingKey) -> Option<Note> {
+        let key = kdf(&ivk.agree(&self.epk), &self.epk);
+        let plaintext = aead::open(&key, &NONCE, &self.cm.0, &self.ciphertext)?;
+        if plaintext.len() != PLAINTEXT_LEN {
+            return None;
+        }
+
+        let diversifier = Diversifier(plaintext[..11].try_into().unwrap());
+        let note = Note {
+            asset_type: AssetType::from_bytes(plaintext[51..].try_into().unwrap()),
+            amount: u64::from_le_bytes(plaintext[11..19].try_into().unwrap()),
+            recipient: ivk.address(diversifier),
+            rcm: Scalar::from_canonical_bytes(&plaintext[19..51].try_into().unwrap())?,
+        };
+        // 復号したノートが公開されたコミットメントと一致しなければ受け取らない
+        (note.commit() == self.cm).then_some(note)
+    }
+}
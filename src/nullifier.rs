@@ -0,0 +1,63 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::commitment::NoteCommitment;
+use crate::hash::hash32;
+use crate::keys::NullifierKey;
+
+// ノートを消費したときに公開される値。同じノートからは常に同じ値が導出される
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Nullifier(pub [u8; 32]);
+
+impl Nullifier {
+    // ヌリファイア導出鍵、ノートコミットメント、ツリー上の位置から導出
+    pub fn derive(nk: &NullifierKey, cm: &NoteCommitment, position: u64) -> Self {
+        Nullifier(hash32(
+            b"MASPsim_nf",
+            &[&nk.0.to_bytes(), &cm.0, &position.to_le_bytes()],
+        ))
+    }
+}
+
+impl fmt::Debug for Nullifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Nullifier(")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// これまでに公開されたヌリファイアの集合。二重支払いの検出に使う
+#[derive(Debug, Clone, Default)]
+pub struct NullifierSet {
+    spent: HashSet<Nullifier>,
+}
+
+impl NullifierSet {
+    pub fn new() -> Self {
+        NullifierSet::default()
+    }
+
+    pub fn contains(&self, nullifier: &Nullifier) -> bool {
+        self.spent.contains(nullifier)
+    }
+
+    // ヌリファイアを記録する。既に記録済みであれば false を返す
+    pub fn insert(&mut self, nullifier: Nullifier) -> bool {
+        self.spent.insert(nullifier)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Nullifier> {
+        self.spent.iter()
+    }
+
+    // 集合の内容だけで決まるダイジェスト。ヌリファイアを昇順に並べてハッシュする
+    pub fn digest(&self) -> [u8; 32] {
+        let mut sorted: Vec<&Nullifier> = self.spent.iter().collect();
+        sorted.sort();
+        let parts: Vec<&[u8]> = sorted.iter().map(|nullifier| &nullifier.0[..]).collect();
+        hash32(b"MASPsim_NfSet", &parts)
+    }
+}
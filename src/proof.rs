@@ -0,0 +1,150 @@
+use std::fmt;
+
+use crate::commitment::{NoteCommitment, ValueCommitment};
+use crate::group::{Point, Scalar};
+use crate::hash::hash32;
+use crate::keys::FullViewingKey;
+use crate::note::Note;
+use crate::nullifier::Nullifier;
+use crate::rng;
+use crate::tree::{Anchor, MerklePath, TREE_DEPTH};
+
+// 証明。検証者には中身の分からないバイト列として扱う
+#[derive(Clone, PartialEq, Eq)]
+pub struct Proof(pub Vec<u8>);
+
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Proof({} bytes)", self.0.len())
+    }
+}
+
+// 証明する命題。公開される値と、それを満たす秘密の証拠 (witness) の関係を定める
+pub trait Statement {
+    type Witness;
+
+    // 証拠が命題を満たすかどうか
+    fn is_satisfied(&self, witness: &Self::Witness) -> bool;
+
+    // 命題の公開される値の表現
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+// 入力の命題: アンカーのツリーに含まれるノートを知っており、そのヌリファイア、値コミットメント、
+// 再ランダム化した検証鍵が正しく導出されている
+#[derive(Debug, Clone)]
+pub struct SpendStatement {
+    pub anchor: Anchor,
+    pub cv: ValueCommitment,
+    pub nullifier: Nullifier,
+    pub rk: Point,
+}
+
+// 入力の証拠
+#[derive(Debug, Clone)]
+pub struct SpendWitness {
+    pub note: Note,
+    pub path: MerklePath,
+    pub fvk: FullViewingKey, // ノートの受取人のアドレスを導出した鍵
+    pub rcv: Scalar,
+    pub alpha: Scalar,
+}
+
+impl Statement for SpendStatement {
+    type Witness = SpendWitness;
+
+    fn is_satisfied(&self, witness: &SpendWitness) -> bool {
+        let note = &witness.note;
+        let cm = note.commit();
+        witness.path.auth_path.len() == TREE_DEPTH
+            && witness.path.root(&cm) == self.anchor
+            && witness.fvk.incoming_viewing_key().owns(&note.recipient)
+            && Nullifier::derive(&witness.fvk.nk, &cm, witness.path.position) == self.nullifier
+            && ValueCommitment::derive(&note.asset_type, note.amount, &witness.rcv) == self.cv
+            && witness.fvk.randomized_ak(&witness.alpha) == self.rk
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            &b"spend"[..],
+            &self.anchor.0,
+            &self.cv.0.to_bytes(),
+            &self.nullifier.0,
+            &self.rk.to_bytes(),
+        ]
+        .concat()
+    }
+}
+
+// 出力の命題: ノートコミットメントと値コミットメントが同じノートから正しく導出されている
+#[derive(Debug, Clone)]
+pub struct OutputStatement {
+    pub cm: NoteCommitment,
+    pub cv: ValueCommitment,
+}
+
+// 出力の証拠
+#[derive(Debug, Clone)]
+pub struct OutputWitness {
+    pub note: Note,
+    pub rcv: Scalar,
+}
+
+impl Statement for OutputStatement {
+    type Witness = OutputWitness;
+
+    fn is_satisfied(&self, witness: &OutputWitness) -> bool {
+        let note = &witness.note;
+        note.commit() == self.cm
+            && ValueCommitment::derive(&note.asset_type, note.amount, &witness.rcv) == self.cv
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [&b"output"[..], &self.cm.0, &self.cv.0.to_bytes()].concat()
+    }
+}
+
+// 証明系。本物のゼロ知識証明の実装に差し替えられるようにする
+pub trait ProofSystem {
+    // 証拠が命題を満たす場合だけ証明を作成する
+    fn prove<S: Statement>(&self, statement: &S, witness: &S::Witness) -> Option<Proof>;
+
+    fn verify<S: Statement>(&self, statement: &S, proof: &Proof) -> bool;
+}
+
+// 証明系の模擬。証拠を直接確認し、セットアップで生成した鍵による命題のMACを証明とする。
+// 鍵を知るのはこの証明系だけなので、証拠なしに正しい証明を作ることはできないものとみなす
+#[derive(Clone)]
+pub struct MockProver {
+    key: [u8; 32],
+}
+
+impl MockProver {
+    pub fn setup() -> Self {
+        let mut key = [0u8; 32];
+        rng::fill_bytes(&mut key);
+        MockProver { key }
+    }
+
+    fn tag<S: Statement>(&self, statement: &S) -> Vec<u8> {
+        hash32(b"MASPsim_MockPrf", &[&self.key, &statement.to_bytes()]).to_vec()
+    }
+}
+
+impl fmt::Debug for MockProver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MockProver(..)")
+    }
+}
+
+impl ProofSystem for MockProver {
+    fn prove<S: Statement>(&self, statement: &S, witness: &S::Witness) -> Option<Proof> {
+        statement
+            .is_satisfied(witness)
+            .then(|| Proof(self.tag(statement)))
+    }
+
+    fn verify<S: Statement>(&self, statement: &S, proof: &Proof) -> bool {
+        proof.0 == self.tag(statement)
+    }
+}
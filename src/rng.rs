@@ -0,0 +1,14 @@
+use std::fs::File;
+use std::io::Read;
+use std::sync::OnceLock;
+
+// OSの乱数源からバイト列を埋める。/dev/urandom を使うのでこのモジュールは Unix 系の OS でのみビルドする。
+// ファイルは一度だけ開いて使い回す
+pub fn fill_bytes(dest: &mut [u8]) {
+    static SOURCE: OnceLock<File> = OnceLock::new();
+    let mut source =
+        SOURCE.get_or_init(|| File::open("/dev/urandom").expect("failed to open /dev/urandom"));
+    source
+        .read_exact(dest)
+        .expect("failed to read from /dev/urandom");
+}
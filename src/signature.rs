@@ -0,0 +1,52 @@
+use crate::group::{Point, Scalar};
+use crate::rng;
+
+// Schnorr署名 (R, s)。生成元 B に対する秘密鍵 sk で署名し、公開鍵 [sk] B で検証する
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub r: Point,
+    pub s: Scalar,
+}
+
+// チャレンジ c = H(R, 公開鍵, メッセージ)
+fn challenge(r: &Point, public_key: &Point, message: &[u8]) -> Scalar {
+    Scalar::hash(
+        b"MASPsim_Sig",
+        &[&r.to_bytes(), &public_key.to_bytes(), message],
+    )
+}
+
+impl Signature {
+    // まだ署名していないことを表す値。単位元の公開鍵に対しては式が成り立つが、
+    // verify は位数の小さい公開鍵を拒否するので検証に失敗する
+    pub const EMPTY: Signature = Signature {
+        r: Point::IDENTITY,
+        s: Scalar::ZERO,
+    };
+
+    // ノンスは秘密鍵、メッセージ、乱数から導出する。s = r + c * sk
+    pub fn sign(base: &Point, secret_key: &Scalar, message: &[u8]) -> Self {
+        let mut entropy = [0u8; 32];
+        rng::fill_bytes(&mut entropy);
+        let nonce = Scalar::hash(
+            b"MASPsim_SigNonce",
+            &[&secret_key.to_bytes(), message, &entropy],
+        );
+        let r = *base * nonce;
+        let c = challenge(&r, &(*base * *secret_key), message);
+        Signature {
+            r,
+            s: nonce + c * *secret_key,
+        }
+    }
+
+    // [s] B = R + [c] 公開鍵 が成り立つかを確認する。[8] 公開鍵 が単位元になる位数の小さい公開鍵では
+    // 誰でも署名を作れてしまうので、式によらず拒否する
+    pub fn verify(&self, base: &Point, public_key: &Point, message: &[u8]) -> bool {
+        if public_key.double().double().double() == Point::IDENTITY {
+            return false;
+        }
+        let c = challenge(&self.r, public_key, message);
+        *base * self.s == self.r + *public_key * c
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::asset::AssetType;
+use crate::commitment::{value_randomness_base, ValueCommitment};
+use crate::encoding;
+use crate::error::BuildError;
+use crate::group::{Point, Scalar};
+use crate::hash::hash32;
+use crate::issuance::{Issuance, IssuanceKey};
+use crate::keys::{spend_auth_base, ExpandedSpendingKey};
+use crate::note::Note;
+use crate::note_encryption::EncryptedNote;
+use crate::nullifier::Nullifier;
+use crate::proof::{
+    OutputStatement, OutputWitness, Proof, ProofSystem, SpendStatement, SpendWitness,
+};
+use crate::signature::Signature;
+use crate::transparent::{TransparentAddress, TransparentInput, TransparentKey, TransparentOutput};
+use crate::tree::Anchor;
+
+// 消費する入力。ノートの内容は隠したまま、ノートを消費済みにするヌリファイアと値コミットメントを公開する
+#[derive(Debug, Clone)]
+pub struct Spend {
+    pub cv: ValueCommitment, // 入力ノートの量に対する値コミットメント
+    pub nullifier: Nullifier,
+    pub rk: Point,    // 再ランダム化した支払い認証の検証鍵
+    pub proof: Proof, // ノートがアンカーのツリーに含まれ、公開する値が正しく導出されていることの証明
+    // rk に対応する鍵によるシグハッシュへの署名。所有者がこの入力の消費を認めたことを示す
+    pub spend_auth_sig: Signature,
+}
+
+// 作成する出力。台帳に公開される暗号化されたノートと、ノートの量に対する値コミットメント
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub cv: ValueCommitment,
+    pub encrypted: EncryptedNote,
+    pub proof: Proof, // cm と cv が同じノートから導出されていることの証明
+}
+
+// 入力を作るための秘密の情報。証拠の完全閲覧鍵が誰の入力かを表し、
+// 1つのトランザクションに複数の所有者の入力を含められる
+#[derive(Debug, Clone)]
+pub struct SpendInfo {
+    pub witness: SpendWitness,
+}
+
+impl SpendInfo {
+    fn statement(&self, anchor: Anchor) -> SpendStatement {
+        let witness = &self.witness;
+        SpendStatement {
+            anchor,
+            cv: ValueCommitment::derive(
+                &witness.note.asset_type,
+                witness.note.amount,
+                &witness.rcv,
+            ),
+            nullifier: Nullifier::derive(
+                &witness.fvk.nk,
+                &witness.note.commit(),
+                witness.path.position,
+            ),
+            rk: witness.fvk.randomized_ak(&witness.alpha),
+        }
+    }
+}
+
+// トランザクションの識別子
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TxId(pub [u8; 32]);
+
+impl fmt::Debug for TxId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TxId(")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// トランザクション構造体の定義。複数の資産タイプの入力と出力を含み、資産タイプごとに収支を合わせる
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub anchor: Anchor,       // 入力ノートの存在を証明する対象のツリーのルート
+    pub inputs: Vec<Spend>,   // 消費するノート
+    pub outputs: Vec<Output>, // 新しく作成されるノート（お釣りを含む）
+    pub transparent_inputs: Vec<TransparentInput>, // 透明なアカウントから引き出す量
+    pub transparent_outputs: Vec<TransparentOutput>, // 透明なアカウントへ入金する量
+    pub issuance: Vec<Issuance>, // 発行者が新しく作る量
+    pub burns: BTreeMap<AssetType, u64>, // 資産タイプごとに破棄する量
+    // 資産タイプごとのシールドされたプールの入力合計と出力合計の差。正の値はプールから出ていく量、
+    // 負の値はプールへ入ってくる量を表す
+    pub value_balance: BTreeMap<AssetType, i64>,
+    pub fee: u64, // 台帳の手数料の資産タイプで払う手数料。ブロックを作った人が受け取る
+    // 値コミットメントの収支が value_balance と一致することを示す署名
+    pub binding_sig: Signature,
+}
+
+impl Transaction {
+    // トランザクションの識別子。証明と署名を含む正規の符号化全体のハッシュ
+    pub fn txid(&self) -> TxId {
+        TxId(hash32(b"MASPsim_TxId", &[&encoding::encode(self)]))
+    }
+
+    // 署名の対象となるトランザクションのハッシュ。証明と署名を空にした正規の符号化のハッシュ
+    pub fn sighash(&self) -> [u8; 32] {
+        let mut unauthorized = self.clone();
+        for input in &mut unauthorized.inputs {
+            input.proof = Proof(Vec::new());
+            input.spend_auth_sig = Signature::EMPTY;
+        }
+        for output in &mut unauthorized.outputs {
+            output.proof = Proof(Vec::new());
+        }
+        for input in &mut unauthorized.transparent_inputs {
+            input.signature = Signature::EMPTY;
+        }
+        for issuance in &mut unauthorized.issuance {
+            issuance.signature = Signature::EMPTY;
+        }
+        unauthorized.binding_sig = Signature::EMPTY;
+        hash32(b"MASPsim_SigHash", &[&encoding::encode(&unauthorized)])
+    }
+
+    // 資産タイプごとの、入ってくる量（透明な入力、シールドされたプールから出ていく量、ミント）から
+    // 出ていく量（透明な出力、バーン、手数料）を引いた余り。手数料は fee_asset の量として引く
+    pub fn remainders(&self, fee_asset: Option<&AssetType>) -> BTreeMap<AssetType, i128> {
+        let mut remainders: BTreeMap<AssetType, i128> = BTreeMap::new();
+        for (asset_type, value) in &self.value_balance {
+            *remainders.entry(*asset_type).or_default() += i128::from(*value);
+        }
+        for input in &self.transparent_inputs {
+            *remainders.entry(input.asset_type).or_default() += i128::from(input.amount);
+        }
+        for issuance in &self.issuance {
+            *remainders.entry(issuance.asset_type).or_default() += i128::from(issuance.amount);
+        }
+        for output in &self.transparent_outputs {
+            *remainders.entry(output.asset_type).or_default() -= i128::from(output.amount);
+        }
+        for (asset_type, amount) in &self.burns {
+            *remainders.entry(*asset_type).or_default() -= i128::from(*amount);
+        }
+        if let Some(fee_asset) = fee_asset {
+            *remainders.entry(*fee_asset).or_default() -= i128::from(self.fee);
+        }
+        remainders
+    }
+
+    // 入ってくる量と出ていく量が一致しない資産タイプ
+    pub fn unbalanced_asset(&self, fee_asset: Option<&AssetType>) -> Option<AssetType> {
+        self.remainders(fee_asset)
+            .into_iter()
+            .find(|(_, value)| *value != 0)
+            .map(|(asset_type, _)| asset_type)
+    }
+
+    // 入力と出力（透明なものを含む）の数。手数料の最低額の計算に使う
+    pub fn actions(&self) -> usize {
+        self.inputs.len()
+            + self.outputs.len()
+            + self.transparent_inputs.len()
+            + self.transparent_outputs.len()
+    }
+
+    // バインディング署名の検証鍵。入力の cv の和から出力の cv の和と公開された値の収支を引いたもの。
+    // 資産タイプごとの収支が合っていれば、量の項が消えて [bsk] R_cv になる
+    pub fn binding_verification_key(&self) -> Point {
+        let mut key = Point::IDENTITY;
+        for input in &self.inputs {
+            key = key + input.cv.0;
+        }
+        for output in &self.outputs {
+            key = key - output.cv.0;
+        }
+        for (asset_type, value) in &self.value_balance {
+            key = key - ValueCommitment::public(asset_type, *value).0;
+        }
+        key
+    }
+
+    pub fn spend_statement(&self, index: usize) -> SpendStatement {
+        let input = &self.inputs[index];
+        SpendStatement {
+            anchor: self.anchor,
+            cv: input.cv,
+            nullifier: input.nullifier,
+            rk: input.rk,
+        }
+    }
+
+    pub fn output_statement(&self, index: usize) -> OutputStatement {
+        let output = &self.outputs[index];
+        OutputStatement {
+            cm: output.encrypted.cm,
+            cv: output.cv,
+        }
+    }
+}
+
+// 証明と署名を付ける前のトランザクション。公開される部分と、それを作るための秘密の情報を保持する
+#[derive(Debug, Clone)]
+pub struct UnauthorizedTransaction {
+    transaction: Transaction,    // 証明と署名はまだ空
+    spends: Vec<SpendInfo>,      // transaction.inputs と同じ順
+    outputs: Vec<OutputWitness>, // transaction.outputs と同じ順
+}
+
+impl UnauthorizedTransaction {
+    // トランザクションの新規作成。アンカー、消費するノート、作成するノートを指定します。
+    pub fn new(anchor: Anchor, spends: Vec<SpendInfo>, outputs: Vec<Note>) -> Self {
+        let inputs = spends
+            .iter()
+            .map(|spend| {
+                let statement = spend.statement(anchor);
+                Spend {
+                    cv: statement.cv,
+                    nullifier: statement.nullifier,
+                    rk: statement.rk,
+                    proof: Proof(Vec::new()),
+                    spend_auth_sig: Signature::EMPTY,
+                }
+            })
+            .collect();
+        let outputs: Vec<OutputWitness> = outputs
+            .into_iter()
+            .map(|note| OutputWitness {
+                note,
+                rcv: Scalar::random(),
+            })
+            .collect();
+        UnauthorizedTransaction {
+            transaction: Transaction {
+                anchor,
+                inputs,
+                outputs: outputs
+                    .iter()
+                    .map(|output| Output {
+                        cv: ValueCommitment::derive(
+                            &output.note.asset_type,
+                            output.note.amount,
+                            &output.rcv,
+                        ),
+                        encrypted: EncryptedNote::encrypt(&output.note),
+                        proof: Proof(Vec::new()),
+                    })
+                    .collect(),
+                transparent_inputs: Vec::new(),
+                transparent_outputs: Vec::new(),
+                issuance: Vec::new(),
+                burns: BTreeMap::new(),
+                value_balance: BTreeMap::new(),
+                fee: 0,
+                binding_sig: Signature::EMPTY,
+            },
+            spends,
+            outputs,
+        }
+    }
+
+    // 資産タイプのシールドされたプールから出ていく量（正の値）または入ってくる量（負の値）を明示的に指定
+    pub fn with_value_balance(mut self, asset_type: AssetType, value: i64) -> Self {
+        self.transaction.value_balance.insert(asset_type, value);
+        self
+    }
+
+    // 透明なアカウントから引き出す入力を追加。nonce はアカウントの現在のノンス
+    pub fn add_transparent_input(
+        mut self,
+        public_key: Point,
+        asset_type: AssetType,
+        amount: u64,
+        nonce: u64,
+    ) -> Self {
+        self.transaction.transparent_inputs.push(TransparentInput {
+            address: TransparentAddress::from_public_key(&public_key),
+            public_key,
+            asset_type,
+            amount,
+            nonce,
+            signature: Signature::EMPTY,
+        });
+        self
+    }
+
+    // 透明なアカウントへ入金する出力を追加
+    pub fn add_transparent_output(
+        mut self,
+        address: TransparentAddress,
+        asset_type: AssetType,
+        amount: u64,
+    ) -> Self {
+        self.transaction
+            .transparent_outputs
+            .push(TransparentOutput {
+                address,
+                asset_type,
+                amount,
+            });
+        self
+    }
+
+    // 発行者 issuer によるミントを追加。署名は sign_issuance で付ける
+    pub fn add_issuance(mut self, issuer: Point, asset_type: AssetType, amount: u64) -> Self {
+        self.transaction.issuance.push(Issuance {
+            asset_type,
+            amount,
+            issuer,
+            signature: Signature::EMPTY,
+        });
+        self
+    }
+
+    // 手数料を指定。手数料の分は with_value_balance でシールドされたプールから出ていく量に含める
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.transaction.fee = fee;
+        self
+    }
+
+    // 資産タイプの量を破棄する。同じ資産タイプを何度も指定すると量を合計し、合計が表現できなければエラー
+    pub fn with_burn(mut self, asset_type: AssetType, amount: u64) -> Result<Self, BuildError> {
+        let burn = self.transaction.burns.entry(asset_type).or_default();
+        *burn = burn
+            .checked_add(amount)
+            .ok_or(BuildError::AmountOverflow(asset_type))?;
+        Ok(self)
+    }
+
+    pub fn sighash(&self) -> [u8; 32] {
+        self.transaction.sighash()
+    }
+
+    // 発行者の鍵によるミントに署名する
+    pub fn sign_issuance(&mut self, key: &IssuanceKey) {
+        let sighash = self.sighash();
+        let issuer = key.public_key();
+        for issuance in &mut self.transaction.issuance {
+            if issuance.issuer == issuer {
+                issuance.signature = key.sign(&sighash);
+            }
+        }
+    }
+
+    // 鍵に対応するアドレスからの透明な入力に署名する
+    pub fn sign_transparent(&mut self, key: &TransparentKey) {
+        let sighash = self.sighash();
+        let address = key.address();
+        for input in &mut self.transaction.transparent_inputs {
+            if input.address == address {
+                input.signature = key.sign(&sighash);
+            }
+        }
+    }
+
+    // 認証鍵 ak が expanded のものと一致する入力に、再ランダム化した鍵でシグハッシュへの署名を付ける。
+    // シグハッシュが変わらないよう、すべての変更を終えてから署名する
+    pub fn sign_spends(&mut self, expanded: &ExpandedSpendingKey) {
+        let sighash = self.sighash();
+        let ak = expanded.full_viewing_key().ak;
+        for (input, spend) in self.transaction.inputs.iter_mut().zip(&self.spends) {
+            if spend.witness.fvk.ak == ak {
+                let rsk = expanded.randomized_ask(&spend.witness.alpha);
+                input.spend_auth_sig = Signature::sign(spend_auth_base(), &rsk, &sighash);
+            }
+        }
+    }
+
+    // 入力と出力の証明を作成し、ブラインディング係数の差 bsk でバインディング署名を付けて完成させる
+    pub fn prove<P: ProofSystem>(self, prover: &P) -> Result<Transaction, BuildError> {
+        let UnauthorizedTransaction {
+            mut transaction,
+            spends,
+            outputs,
+        } = self;
+
+        let mut bsk = Scalar::ZERO;
+        for (index, spend) in spends.iter().enumerate() {
+            let statement = transaction.spend_statement(index);
+            transaction.inputs[index].proof = prover
+                .prove(&statement, &spend.witness)
+                .ok_or(BuildError::ProofFailed)?;
+            bsk = bsk + spend.witness.rcv;
+        }
+        for (index, output) in outputs.iter().enumerate() {
+            let statement = transaction.output_statement(index);
+            transaction.outputs[index].proof = prover
+                .prove(&statement, output)
+                .ok_or(BuildError::ProofFailed)?;
+            bsk = bsk - output.rcv;
+        }
+
+        transaction.binding_sig =
+            Signature::sign(value_randomness_base(), &bsk, &transaction.sighash());
+        Ok(transaction)
+    }
+
+    // 資産タイプごとのシールドされた側の収支が value_balance と一致するかを平文の量から確認し、一致しない資産タイプを返す。
+    // 組み立てる側の確認用で、検証者は代わりにバインディング署名を確認する
+    pub fn value_imbalance(&self) -> Option<AssetType> {
+        let mut balance: BTreeMap<AssetType, i128> = BTreeMap::new();
+        for spend in &self.spends {
+            let note = &spend.witness.note;
+            *balance.entry(note.asset_type).or_default() += i128::from(note.amount);
+        }
+        for output in &self.outputs {
+            *balance.entry(output.note.asset_type).or_default() -= i128::from(output.note.amount);
+        }
+        for (asset_type, value) in &self.transaction.value_balance {
+            *balance.entry(*asset_type).or_default() -= i128::from(*value);
+        }
+        balance
+            .into_iter()
+            .find(|(_, value)| *value != 0)
+            .map(|(asset_type, _)| asset_type)
+    }
+}
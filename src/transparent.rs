@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::asset::AssetType;
+use crate::group::{Point, Scalar};
+use crate::hash::hash32;
+use crate::signature::Signature;
+
+// 透明なアカウントの署名に使う生成元
+pub fn transparent_base() -> &'static Point {
+    static BASE: OnceLock<Point> = OnceLock::new();
+    BASE.get_or_init(|| Point::hash_to_point(b"MASPsim_KeyGen", b"Transparent"))
+}
+
+// 透明なアドレス。公開鍵のハッシュ
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TransparentAddress(pub [u8; 32]);
+
+impl TransparentAddress {
+    pub fn from_public_key(public_key: &Point) -> Self {
+        TransparentAddress(hash32(b"MASPsim_TAddr", &[&public_key.to_bytes()]))
+    }
+}
+
+impl fmt::Debug for TransparentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TransparentAddress(")?;
+        for byte in &self.0[..8] {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, "..)")
+    }
+}
+
+// 透明なアカウントの秘密鍵
+#[derive(Clone)]
+pub struct TransparentKey(Scalar);
+
+impl TransparentKey {
+    pub fn random() -> Self {
+        TransparentKey(Scalar::random())
+    }
+
+    pub fn public_key(&self) -> Point {
+        *transparent_base() * self.0
+    }
+
+    pub fn address(&self) -> TransparentAddress {
+        TransparentAddress::from_public_key(&self.public_key())
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        Signature::sign(transparent_base(), &self.0, message)
+    }
+}
+
+impl fmt::Debug for TransparentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TransparentKey(..)")
+    }
+}
+
+// 透明なアカウントから引き出す入力。量と資産タイプは公開され、アカウントの鍵による署名で認可する
+#[derive(Debug, Clone)]
+pub struct TransparentInput {
+    pub address: TransparentAddress,
+    pub public_key: Point, // アドレスに対応する公開鍵
+    pub asset_type: AssetType,
+    pub amount: u64,
+    pub nonce: u64, // 同じ入力の再利用を防ぐための、アカウントの現在のノンス
+    pub signature: Signature,
+}
+
+// 透明なアカウントへ入金する出力
+#[derive(Debug, Clone)]
+pub struct TransparentOutput {
+    pub address: TransparentAddress,
+    pub asset_type: AssetType,
+    pub amount: u64,
+}
+
+// 透明なアカウント。資産タイプごとの残高と、使用済みの入力の数
+#[derive(Debug, Clone, Default)]
+pub struct TransparentAccount {
+    pub balances: BTreeMap<AssetType, u64>,
+    pub nonce: u64,
+}
+
+// アドレスをキーとする透明なアカウントの一覧
+#[derive(Debug, Clone, Default)]
+pub struct TransparentLedger {
+    accounts: HashMap<TransparentAddress, TransparentAccount>,
+}
+
+impl TransparentLedger {
+    pub fn new() -> Self {
+        TransparentLedger::default()
+    }
+
+    pub fn account(&self, address: &TransparentAddress) -> Option<&TransparentAccount> {
+        self.accounts.get(address)
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = (&TransparentAddress, &TransparentAccount)> {
+        self.accounts.iter()
+    }
+
+    pub fn balance(&self, address: &TransparentAddress, asset_type: &AssetType) -> u64 {
+        self.account(address)
+            .and_then(|account| account.balances.get(asset_type))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn nonce(&self, address: &TransparentAddress) -> u64 {
+        self.account(address).map_or(0, |account| account.nonce)
+    }
+
+    pub fn set_balance(&mut self, address: TransparentAddress, asset_type: AssetType, amount: u64) {
+        self.accounts
+            .entry(address)
+            .or_default()
+            .balances
+            .insert(asset_type, amount);
+    }
+
+    // アカウントを丸ごと置き換える
+    pub fn insert_account(&mut self, address: TransparentAddress, account: TransparentAccount) {
+        self.accounts.insert(address, account);
+    }
+
+    // 入力を使ったアカウントのノンスを進める
+    pub fn increment_nonce(&mut self, address: TransparentAddress) {
+        self.accounts.entry(address).or_default().nonce += 1;
+    }
+}
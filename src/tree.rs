@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::commitment::NoteCommitment;
+use crate::hash::hash32;
+
+// コミットメントツリーの深さ。最大 2^32 個のノートを格納できる
+pub const TREE_DEPTH: usize = 32;
+
+// ツリーのルート。トランザクションは入力ノートの存在をこのアンカーに対して証明する
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anchor(pub [u8; 32]);
+
+impl fmt::Debug for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Anchor(")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+// 空の葉。素数位数の部分群に含まれない点の表現なので、実際のコミットメントとは衝突しない
+const EMPTY_LEAF: [u8; 32] = [0; 32];
+
+// 高さ level の2つの子ノードから親ノードを計算
+fn merkle_hash(level: usize, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hash32(b"MASPsim_Merkle", &[&[level as u8], left, right])
+}
+
+// 高さごとの空の部分木のルート
+fn empty_roots() -> Vec<[u8; 32]> {
+    let mut roots = vec![EMPTY_LEAF];
+    for level in 0..TREE_DEPTH {
+        let below = roots[level];
+        roots.push(merkle_hash(level, &below, &below));
+    }
+    roots
+}
+
+// ノートが位置 position の葉としてツリーに含まれることを示す認証パス
+#[derive(Debug, Clone)]
+pub struct MerklePath {
+    pub position: u64,
+    pub auth_path: Vec<[u8; 32]>, // 葉に近い側から並べた兄弟ノード
+}
+
+impl MerklePath {
+    // コミットメントを葉として、認証パスからルートを計算
+    pub fn root(&self, cm: &NoteCommitment) -> Anchor {
+        let mut node = cm.0;
+        for (level, sibling) in self.auth_path.iter().enumerate() {
+            node = if (self.position >> level) & 1 == 0 {
+                merkle_hash(level, &node, sibling)
+            } else {
+                merkle_hash(level, sibling, &node)
+            };
+        }
+        Anchor(node)
+    }
+}
+
+// すべての出力ノートのコミットメントを追記していく、深さ固定のインクリメンタルなマークルツリー
+#[derive(Debug, Clone)]
+pub struct CommitmentTree {
+    // levels[0] は葉、levels[h] は高さ h の計算済みノード（左詰め）
+    levels: Vec<Vec<[u8; 32]>>,
+    empty_roots: Vec<[u8; 32]>,
+    // チェックポイントで記録したルート
+    anchors: HashSet<Anchor>,
+}
+
+impl Default for CommitmentTree {
+    fn default() -> Self {
+        CommitmentTree::new()
+    }
+}
+
+impl CommitmentTree {
+    pub fn new() -> Self {
+        let mut tree = CommitmentTree {
+            levels: vec![Vec::new(); TREE_DEPTH + 1],
+            empty_roots: empty_roots(),
+            anchors: HashSet::new(),
+        };
+        tree.checkpoint();
+        tree
+    }
+
+    // 格納済みのノート数
+    pub fn size(&self) -> u64 {
+        self.levels[0].len() as u64
+    }
+
+    // コミットメントを追記し、その葉の位置を返す。葉からルートまでのノードだけを更新する
+    pub fn append(&mut self, cm: NoteCommitment) -> u64 {
+        let position = self.size();
+        assert!(position < 1 << TREE_DEPTH, "commitment tree is full");
+        self.levels[0].push(cm.0);
+
+        let mut index = position as usize;
+        for level in 0..TREE_DEPTH {
+            let left_index = index & !1;
+            let left = self.levels[level][left_index];
+            let right = self.levels[level]
+                .get(left_index + 1)
+                .copied()
+                .unwrap_or(self.empty_roots[level]);
+            let parent = merkle_hash(level, &left, &right);
+
+            index >>= 1;
+            let above = &mut self.levels[level + 1];
+            if index < above.len() {
+                above[index] = parent;
+            } else {
+                above.push(parent);
+            }
+        }
+
+        position
+    }
+
+    // 現在のルートをアンカーとして記録する。トランザクションの出力をすべて追記し終えたときに呼び、
+    // 途中のルートはアンカーにしない
+    pub fn checkpoint(&mut self) -> Anchor {
+        let root = self.root();
+        self.anchors.insert(root);
+        root
+    }
+
+    // 現在のルート
+    pub fn root(&self) -> Anchor {
+        Anchor(
+            self.levels[TREE_DEPTH]
+                .first()
+                .copied()
+                .unwrap_or(self.empty_roots[TREE_DEPTH]),
+        )
+    }
+
+    // 過去にチェックポイントで記録したルートかどうか
+    pub fn is_known_anchor(&self, anchor: &Anchor) -> bool {
+        self.anchors.contains(anchor)
+    }
+
+    // 位置 position の葉について、現在のルートに対する認証パスを返す
+    pub fn witness(&self, position: u64) -> Option<MerklePath> {
+        if position >= self.size() {
+            return None;
+        }
+        let auth_path = (0..TREE_DEPTH)
+            .map(|level| {
+                let sibling = ((position >> level) ^ 1) as usize;
+                self.levels[level]
+                    .get(sibling)
+                    .copied()
+                    .unwrap_or(self.empty_roots[level])
+            })
+            .collect();
+        Some(MerklePath {
+            position,
+            auth_path,
+        })
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::asset::AssetType;
+use crate::block::{Block, BlockHash};
+use crate::error::{BlockError, BuildError};
+use crate::fee::FeeRule;
+use crate::group::{Point, Scalar};
+use crate::keys::{Diversifier, FullViewingKey, IncomingViewingKey, PaymentAddress, SpendingKey};
+use crate::note::Note;
+use crate::nullifier::Nullifier;
+use crate::proof::{ProofSystem, SpendWitness};
+use crate::transaction::{SpendInfo, Transaction, TxId, UnauthorizedTransaction};
+use crate::transparent::{TransparentAddress, TransparentKey};
+use crate::tree::{Anchor, CommitmentTree, MerklePath};
+
+// ウォレットが受け取ったノート。コミットメントツリー上の位置、ヌリファイア、
+// 同期したツリーのルートに対する認証パスと、消費済みかどうかを保持
+#[derive(Debug, Clone)]
+pub struct ReceivedNote {
+    pub position: u64,
+    pub note: Note,
+    pub nullifier: Nullifier,
+    pub witness: MerklePath, // 未消費のノートは同期のたびに更新する
+    pub spent: bool,
+}
+
+// ウォレットのノートの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteStatus {
+    Spendable,    // 未消費で、送信中のトランザクションにも使われていない
+    PendingSpend, // 送信中のトランザクションで消費される
+    Spent,        // ブロックに含まれたトランザクションで消費された
+}
+
+// ノートの一覧を絞り込む条件。指定しなかった条件ではすべてのノートが当てはまる
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoteFilter {
+    asset_type: Option<AssetType>,
+    status: Option<NoteStatus>,
+    min_amount: u64,
+}
+
+impl NoteFilter {
+    pub fn new() -> Self {
+        NoteFilter::default()
+    }
+
+    pub fn with_asset_type(mut self, asset_type: AssetType) -> Self {
+        self.asset_type = Some(asset_type);
+        self
+    }
+
+    pub fn with_status(mut self, status: NoteStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    // amount 以上のノートだけにする
+    pub fn with_min_amount(mut self, amount: u64) -> Self {
+        self.min_amount = amount;
+        self
+    }
+}
+
+// 資産タイプごとの残高。送信中のトランザクションで消費されるノートはどちらにも含めない
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Balance {
+    pub spendable: u64, // 今すぐ使えるノートの合計
+    pub pending: u64, // 送信中のトランザクションから戻ってくる、まだブロックに含まれていないお釣りの合計
+}
+
+impl Balance {
+    // 送信中のトランザクションがすべてブロックに含まれたあとの残高。表現できなければ None
+    pub fn total(&self) -> Option<u64> {
+        self.spendable.checked_add(self.pending)
+    }
+}
+
+// 送信したがまだブロックに含まれていないトランザクション
+#[derive(Debug, Clone)]
+struct SentTransaction {
+    txid: TxId,
+    nullifiers: Vec<Nullifier>, // 消費する自分のノートのヌリファイア
+    change: Vec<Note>,          // 自分宛ての出力
+}
+
+// ウォレット。ユーザーIDと鍵、ブロックを走査して見つけた自分宛てのノートを保持する。
+// 台帳とは別に、ブロックに含まれるコミットメントからツリーを組み立てて認証パスを作る
+#[derive(Debug, Clone)]
+pub struct Wallet {
+    pub id: String,
+    spending_key: SpendingKey,
+    transparent_key: TransparentKey, // 透明なアカウントの鍵
+    tree: CommitmentTree,
+    notes: Vec<ReceivedNote>,      // 受け取った順
+    pending: Vec<SentTransaction>, // 送信した順
+    height: u64,                   // 同期済みのブロックの高さ
+    tip: BlockHash,                // 同期済みの最後のブロックのハッシュ
+}
+
+impl Wallet {
+    // ブロックを1つも同期していないウォレットの新規作成
+    pub fn new(id: &str) -> Self {
+        Wallet {
+            id: id.to_string(),
+            spending_key: SpendingKey::random(),
+            transparent_key: TransparentKey::random(),
+            tree: CommitmentTree::new(),
+            notes: Vec::new(),
+            pending: Vec::new(),
+            height: 0,
+            tip: BlockHash::GENESIS_PARENT,
+        }
+    }
+
+    pub fn full_viewing_key(&self) -> FullViewingKey {
+        self.spending_key.full_viewing_key()
+    }
+
+    pub fn incoming_viewing_key(&self) -> IncomingViewingKey {
+        self.full_viewing_key().incoming_viewing_key()
+    }
+
+    // ノートを受け取るための既定の支払いアドレス
+    pub fn address(&self) -> PaymentAddress {
+        self.incoming_viewing_key()
+            .address(Diversifier::from_index(0))
+    }
+
+    // 透明なアカウントのアドレスと公開鍵
+    pub fn transparent_address(&self) -> TransparentAddress {
+        self.transparent_key.address()
+    }
+
+    pub fn transparent_public_key(&self) -> Point {
+        self.transparent_key.public_key()
+    }
+
+    // 支払い鍵とツリー上の位置から、ノートのヌリファイアを導出
+    pub fn nullifier(&self, note: &Note, position: u64) -> Nullifier {
+        let nk = self.full_viewing_key().nk;
+        Nullifier::derive(&nk, &note.commit(), position)
+    }
+
+    // 同期済みのブロックの高さ
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    // 同期したツリーのルート。このウォレットのノートを消費するトランザクションのアンカーになる
+    pub fn anchor(&self) -> Anchor {
+        self.tree.root()
+    }
+
+    // 受け取ったすべてのノート。消費済みのものを含む
+    pub fn notes(&self) -> &[ReceivedNote] {
+        &self.notes
+    }
+
+    pub fn unspent_notes(&self) -> impl Iterator<Item = &ReceivedNote> {
+        self.notes.iter().filter(|received| !received.spent)
+    }
+
+    pub fn note_status(&self, received: &ReceivedNote) -> NoteStatus {
+        if received.spent {
+            NoteStatus::Spent
+        } else if self
+            .pending
+            .iter()
+            .any(|sent| sent.nullifiers.contains(&received.nullifier))
+        {
+            NoteStatus::PendingSpend
+        } else {
+            NoteStatus::Spendable
+        }
+    }
+
+    // 新しいトランザクションの入力に使えるノート
+    pub fn spendable_notes(&self) -> impl Iterator<Item = &ReceivedNote> {
+        self.notes
+            .iter()
+            .filter(|received| self.note_status(received) == NoteStatus::Spendable)
+    }
+
+    // 条件に当てはまるノートを受け取った順に返す
+    pub fn list_notes(&self, filter: &NoteFilter) -> Vec<&ReceivedNote> {
+        self.notes
+            .iter()
+            .filter(|received| {
+                filter
+                    .asset_type
+                    .is_none_or(|asset_type| received.note.asset_type == asset_type)
+                    && filter
+                        .status
+                        .is_none_or(|status| self.note_status(received) == status)
+                    && received.note.amount >= filter.min_amount
+            })
+            .collect()
+    }
+
+    // 送信中のトランザクションから戻ってくるお釣りのノート
+    pub fn pending_change(&self) -> impl Iterator<Item = &Note> {
+        self.pending.iter().flat_map(|sent| &sent.change)
+    }
+
+    // 資産タイプの残高
+    pub fn balance(&self, asset_type: &AssetType) -> Result<Balance, BuildError> {
+        Ok(self
+            .balances()?
+            .get(asset_type)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    // ノートかお釣りを持つすべての資産タイプの残高。合計が表現できなければ AmountOverflow
+    pub fn balances(&self) -> Result<BTreeMap<AssetType, Balance>, BuildError> {
+        let mut balances: BTreeMap<AssetType, Balance> = BTreeMap::new();
+        for received in self.spendable_notes() {
+            let asset_type = received.note.asset_type;
+            let balance = balances.entry(asset_type).or_default();
+            balance.spendable = balance
+                .spendable
+                .checked_add(received.note.amount)
+                .ok_or(BuildError::AmountOverflow(asset_type))?;
+        }
+        for note in self.pending_change() {
+            let balance = balances.entry(note.asset_type).or_default();
+            balance.pending = balance
+                .pending
+                .checked_add(note.amount)
+                .ok_or(BuildError::AmountOverflow(note.asset_type))?;
+        }
+        Ok(balances)
+    }
+
+    // 送信したトランザクションを、ブロックに含まれるまで記録する。
+    // 消費する自分のノートは新しいトランザクションに使わず、自分宛ての出力をお釣りとして数える
+    pub fn mark_pending(&mut self, transaction: &Transaction) -> TxId {
+        let txid = transaction.txid();
+        let ivk = self.incoming_viewing_key();
+        let nullifiers = transaction
+            .inputs
+            .iter()
+            .map(|input| input.nullifier)
+            .filter(|nullifier| {
+                self.notes
+                    .iter()
+                    .any(|received| received.nullifier == *nullifier)
+            })
+            .collect();
+        let change = transaction
+            .outputs
+            .iter()
+            .filter_map(|output| output.encrypted.try_decrypt(&ivk))
+            .collect();
+        self.pending.retain(|sent| sent.txid != txid);
+        self.pending.push(SentTransaction {
+            txid,
+            nullifiers,
+            change,
+        });
+        txid
+    }
+
+    // ブロックに含まれなくなった送信中のトランザクションの記録を取り消し、消費するはずだったノートを使えるように戻す
+    pub fn cancel_pending(&mut self, txid: &TxId) -> bool {
+        let before = self.pending.len();
+        self.pending.retain(|sent| sent.txid != *txid);
+        self.pending.len() < before
+    }
+
+    // 次のブロックを走査する。出力のコミットメントをツリーに追記しながら受信閲覧鍵で試しに復号して自分宛てのノートを見つけ、
+    // 公開されたヌリファイアから消費済みのノートを記録し、未消費のノートの認証パスを更新する。
+    // 送信中のトランザクションは、ブロックに含まれたか、入力が別のトランザクションで消費されたら記録から外す。
+    // 同期済みのブロックにつながらないか、追記したツリーのルートがヘッダと一致しなければ、何も変更せずに拒否する
+    pub fn sync(&mut self, block: &Block) -> Result<(), BlockError> {
+        let header = &block.header;
+        if header.height != self.height + 1 {
+            return Err(BlockError::WrongHeight {
+                expected: self.height + 1,
+                found: header.height,
+            });
+        }
+        if header.prev_hash != self.tip {
+            return Err(BlockError::UnknownParent(header.prev_hash));
+        }
+
+        let ivk = self.incoming_viewing_key();
+        let mut tree = self.tree.clone();
+        let mut received = Vec::new();
+        let mut revealed = HashSet::new();
+        let mut included = HashSet::new();
+        for transaction in &block.transactions {
+            included.insert(transaction.txid());
+            revealed.extend(transaction.inputs.iter().map(|input| input.nullifier));
+            for output in &transaction.outputs {
+                let position = tree.append(output.encrypted.cm);
+                if let Some(note) = output.encrypted.try_decrypt(&ivk) {
+                    received.push((position, note));
+                }
+            }
+        }
+        if tree.root() != header.tree_root {
+            return Err(BlockError::TreeRootMismatch(header.tree_root));
+        }
+
+        self.tree = tree;
+        self.height = header.height;
+        self.tip = block.hash();
+        for (position, note) in received {
+            let nullifier = self.nullifier(&note, position);
+            self.notes.push(ReceivedNote {
+                position,
+                note,
+                nullifier,
+                witness: self
+                    .tree
+                    .witness(position)
+                    .expect("received notes are in the tree"),
+                spent: false,
+            });
+        }
+        for received in &mut self.notes {
+            if revealed.contains(&received.nullifier) {
+                received.spent = true;
+            } else if !received.spent {
+                received.witness = self
+                    .tree
+                    .witness(received.position)
+                    .expect("received notes are in the tree");
+            }
+        }
+        self.pending.retain(|sent| {
+            !included.contains(&sent.txid)
+                && !sent
+                    .nullifiers
+                    .iter()
+                    .any(|nullifier| revealed.contains(nullifier))
+        });
+        Ok(())
+    }
+
+    // 所有するノートを、同期したツリーに対する認証パス付きの入力にする。署名は authorize で付ける
+    pub fn spend(&self, received: &ReceivedNote) -> SpendInfo {
+        SpendInfo {
+            witness: SpendWitness {
+                note: received.note.clone(),
+                path: received.witness.clone(),
+                fvk: self.full_viewing_key(),
+                rcv: Scalar::random(),
+                alpha: Scalar::random(),
+            },
+        }
+    }
+
+    // トランザクションに含まれる自分の入力に、再ランダム化した鍵でシグハッシュへの署名を付ける。
+    // 自分の透明なアカウントからの入力にも署名する
+    pub fn authorize(&self, transaction: &mut UnauthorizedTransaction) {
+        transaction.sign_spends(&self.spending_key.expand());
+        transaction.sign_transparent(&self.transparent_key);
+    }
+
+    // 特定の資産タイプの使えるノートを統合し、1つの新しいノートを作成する自分宛てのトランザクション。
+    // 規則があれば手数料を統合するノートから払う。統合するノートがない場合、手数料の資産タイプが異なるか
+    // 手数料を払いきれない場合は None。ノートの合計が表現できなければ AmountOverflow、証明を作れなければ ProofFailed
+    pub fn merge_notes<P: ProofSystem>(
+        &self,
+        asset_type: AssetType,
+        fee_rule: Option<&FeeRule>,
+        prover: &P,
+    ) -> Result<Option<Transaction>, BuildError> {
+        let received: Vec<&ReceivedNote> = self
+            .spendable_notes()
+            .filter(|received| received.note.asset_type == asset_type)
+            .collect();
+        let amount = received
+            .iter()
+            .try_fold(0u64, |sum, received| sum.checked_add(received.note.amount))
+            .ok_or(BuildError::AmountOverflow(asset_type))?;
+        let fee = match fee_rule {
+            Some(fee_rule) if fee_rule.asset_type != asset_type => return Ok(None),
+            Some(fee_rule) => fee_rule.minimum(received.len() + 1),
+            None => 0,
+        };
+        let Some(amount) = amount.checked_sub(fee).filter(|amount| *amount > 0) else {
+            return Ok(None);
+        };
+        let mut transaction = UnauthorizedTransaction::new(
+            self.anchor(),
+            received
+                .into_iter()
+                .map(|received| self.spend(received))
+                .collect(),
+            vec![Note::new(asset_type, amount, self.address())],
+        );
+        if fee > 0 {
+            let value = i64::try_from(fee).map_err(|_| BuildError::AmountOverflow(asset_type))?;
+            transaction = transaction
+                .with_value_balance(asset_type, value)
+                .with_fee(fee);
+        }
+        self.authorize(&mut transaction);
+        transaction.prove(prover).map(Some)
+    }
+}
@@ -0,0 +1,22 @@
+use masp_simulation::asset::{AssetRegistry, AssetType};
+
+#[test]
+fn lookup_distinguishes_decimals_and_epochs() {
+    let mut registry = AssetRegistry::new();
+    let coarse = registry.register("BTC", 0, None);
+    let fine = registry.register("BTC", 8, None);
+    let epoch = registry.register("BTC", 8, Some(1));
+    assert_ne!(coarse, fine);
+
+    // 同じ名前で複数登録されていても、メタデータが一致する資産だけを返す
+    assert_eq!(registry.lookup("BTC", 0, None), Some(coarse));
+    assert_eq!(registry.lookup("BTC", 8, None), Some(fine));
+    assert_eq!(registry.lookup("BTC", 8, Some(1)), Some(epoch));
+    assert_eq!(
+        registry.lookup("BTC", 8, None),
+        Some(AssetType::new("BTC", 8, None))
+    );
+    assert_eq!(registry.lookup("BTC", 18, None), None);
+    assert_eq!(registry.lookup("BTC", 0, Some(1)), None);
+    assert_eq!(registry.lookup("ETH", 8, None), None);
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use masp_simulation::block::{Block, BlockHash};
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::encoding;
+use masp_simulation::error::BlockError;
+use masp_simulation::transaction::Transaction;
+use masp_simulation::wallet::Wallet;
+
+use common::{Setup, COIN, PRODUCER};
+
+impl Setup {
+    // 発行者が recipient へ amount をミントするトランザクション
+    fn mint_to(&self, recipient: &Wallet, amount: u64) -> Transaction {
+        self.mint(&[(recipient.address(), self.btc, amount)])
+    }
+
+    // AliceからBobへの送金
+    fn alice_pays_bob(&self, amount: u64) -> Transaction {
+        TransactionBuilder::new(&self.alice)
+            .add_output(self.bob.address(), self.btc, amount)
+            .build(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+}
+
+#[test]
+fn replicas_follow_the_same_chain_of_headers() {
+    let mut setup = Setup::new();
+    // ブロックを1つも適用していない複製
+    let genesis = setup.chain.clone();
+    let mint = setup.mint_to(&setup.alice, 5 * COIN);
+    setup.chain.produce_block(vec![mint], PRODUCER).unwrap();
+    setup.sync();
+    let payment = setup.alice_pays_bob(2 * COIN);
+    let mint = setup.mint_to(&setup.bob, COIN);
+    setup
+        .chain
+        .produce_block(vec![payment, mint], PRODUCER)
+        .unwrap();
+
+    let blocks = setup.chain.blocks();
+    assert_eq!(blocks[0].header.height, 1);
+    assert_eq!(blocks[0].header.prev_hash, BlockHash::GENESIS_PARENT);
+    assert_eq!(blocks[1].header.height, 2);
+    assert_eq!(blocks[1].header.prev_hash, blocks[0].hash());
+    assert_eq!(
+        blocks[1].header.tree_root,
+        setup.chain.ledger().tree().root()
+    );
+
+    // 符号化して送られたブロックを、別の複製が順に検証して適用する
+    let mut replica = genesis;
+    for block in blocks {
+        let block: Block = encoding::decode(&encoding::encode(block)).unwrap();
+        replica.apply_block(&block).unwrap();
+    }
+    assert_eq!(replica.tip_hash(), setup.chain.tip_hash());
+    assert_eq!(
+        replica.ledger().tree().root(),
+        setup.chain.ledger().tree().root()
+    );
+    assert_eq!(
+        replica.ledger().nullifiers().digest(),
+        setup.chain.ledger().nullifiers().digest()
+    );
+}
+
+#[test]
+fn rejects_blocks_that_do_not_match_the_tip_or_their_contents() {
+    let mut setup = Setup::new();
+    let block = setup
+        .chain
+        .build_block(vec![setup.mint_to(&setup.alice, 5 * COIN)], PRODUCER)
+        .unwrap();
+    // 組み立てただけではチェーンは進まない
+    assert_eq!(setup.chain.height(), 0);
+
+    let mut skipped = block.clone();
+    skipped.header.height = 2;
+    assert_eq!(
+        setup.chain.apply_block(&skipped),
+        Err(BlockError::WrongHeight {
+            expected: 1,
+            found: 2
+        })
+    );
+
+    let mut orphan = block.clone();
+    orphan.header.prev_hash = BlockHash([1; 32]);
+    assert_eq!(
+        setup.chain.apply_block(&orphan),
+        Err(BlockError::UnknownParent(BlockHash([1; 32])))
+    );
+
+    let mut emptied = block.clone();
+    emptied.transactions.clear();
+    assert_eq!(
+        setup.chain.apply_block(&emptied),
+        Err(BlockError::TransactionsDigestMismatch)
+    );
+
+    let mut forged_root = block.clone();
+    forged_root.header.tree_root = setup.chain.ledger().tree().root();
+    assert_eq!(
+        setup.chain.apply_block(&forged_root),
+        Err(BlockError::TreeRootMismatch(forged_root.header.tree_root))
+    );
+
+    assert_eq!(setup.chain.height(), 0);
+    assert_eq!(setup.chain.tip_hash(), BlockHash::GENESIS_PARENT);
+    setup.chain.apply_block(&block).unwrap();
+    assert_eq!(setup.chain.tip_hash(), block.hash());
+    assert_eq!(
+        setup.chain.apply_block(&block),
+        Err(BlockError::WrongHeight {
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[test]
+fn rejects_invalid_transactions_without_changing_the_chain() {
+    let mut setup = Setup::new();
+    let mint = setup.mint_to(&setup.alice, 5 * COIN);
+    setup.chain.produce_block(vec![mint], PRODUCER).unwrap();
+    setup.sync();
+
+    // 同じノートを2回消費するブロックは、2つ目のトランザクションで拒否される
+    let payment = setup.alice_pays_bob(COIN);
+    let tip = setup.chain.tip_hash();
+    let result = setup
+        .chain
+        .produce_block(vec![payment.clone(), payment], PRODUCER);
+    assert!(matches!(result, Err(BlockError::InvalidTransaction(1, _))));
+    assert_eq!(setup.chain.tip_hash(), tip);
+    assert!(setup.chain.ledger().nullifiers().iter().next().is_none());
+}
+
+#[test]
+fn spends_must_use_a_recent_anchor() {
+    let mut setup = Setup::new();
+    setup.chain = setup.chain.clone().with_anchor_window(2);
+    let mint = setup.mint_to(&setup.alice, 5 * COIN);
+    setup.chain.produce_block(vec![mint], PRODUCER).unwrap();
+    setup.sync();
+    let payment = setup.alice_pays_bob(COIN);
+
+    // ツリーのルートを変えるブロックが2つ続くと、古いルートはアンカーとして使えなくなる
+    for _ in 0..2 {
+        setup.sync();
+        let mint = setup.mint_to(&setup.bob, COIN);
+        setup.chain.produce_block(vec![mint], PRODUCER).unwrap();
+    }
+    assert!(!setup.chain.is_recent_anchor(&payment.anchor));
+    assert_eq!(
+        setup
+            .chain
+            .produce_block(vec![payment.clone()], PRODUCER)
+            .err(),
+        Some(BlockError::StaleAnchor(0, payment.anchor))
+    );
+
+    // ウォレットを先頭まで同期し、現在のルートに対して作り直せば受け付けられる
+    setup.sync();
+    let payment = setup.alice_pays_bob(COIN);
+    setup.chain.produce_block(vec![payment], PRODUCER).unwrap();
+}
@@ -0,0 +1,101 @@
+// 結合テストで共有する定数とチェーンの組み立て。ファイルごとに使うものが違うので、使わない項目は警告しない
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use masp_simulation::asset::{AssetRegistry, AssetType};
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::chain::Chain;
+use masp_simulation::fee::FeeRule;
+use masp_simulation::issuance::IssuanceKey;
+use masp_simulation::keys::PaymentAddress;
+use masp_simulation::ledger::Ledger;
+use masp_simulation::proof::MockProver;
+use masp_simulation::transaction::Transaction;
+use masp_simulation::transparent::TransparentAddress;
+use masp_simulation::wallet::Wallet;
+
+pub const COIN: u64 = 100_000_000;
+
+// ブロックを作るアドレス。手数料の規則があるチェーンでは、手数料もこのアドレスが受け取る
+pub const PRODUCER: TransparentAddress = TransparentAddress([7; 32]);
+
+// BTC（小数点以下8桁）とETH（18桁）を登録し、どちらにも同じ発行者の鍵を紐付けた登録簿
+pub fn registry() -> (AssetRegistry, IssuanceKey) {
+    let mut registry = AssetRegistry::new();
+    let issuer_key = IssuanceKey::random();
+    for (name, decimals) in [("BTC", 8), ("ETH", 18)] {
+        let asset_type = registry.register(name, decimals, None);
+        registry.set_issuer(&asset_type, issuer_key.public_key());
+    }
+    (registry, issuer_key)
+}
+
+// ブロックのないチェーンと、まだ何も持たない発行者、Alice、Bobのウォレット
+pub struct Setup {
+    pub chain: Chain,
+    pub btc: AssetType,
+    pub eth: AssetType,
+    pub issuer: Wallet,
+    pub issuer_key: IssuanceKey,
+    pub alice: Wallet,
+    pub bob: Wallet,
+}
+
+impl Setup {
+    // 手数料の規則のないチェーン
+    pub fn new() -> Self {
+        let (registry, issuer_key) = registry();
+        Setup::from_ledger(Ledger::new(registry, MockProver::setup()), issuer_key)
+    }
+
+    // BTCで base + 1アクションあたり per_action の手数料を払うチェーン
+    pub fn with_fee_rule(base: u64, per_action: u64) -> Self {
+        let (registry, issuer_key) = registry();
+        let btc = AssetType::new("BTC", 8, None);
+        let ledger = Ledger::new(registry, MockProver::setup())
+            .with_fee_rule(FeeRule::new(btc, base, per_action));
+        Setup::from_ledger(ledger, issuer_key)
+    }
+
+    // registry() の登録簿に資産を足すなどして作った台帳から組み立てる
+    pub fn from_ledger(ledger: Ledger, issuer_key: IssuanceKey) -> Self {
+        Setup {
+            chain: Chain::new(ledger),
+            btc: AssetType::new("BTC", 8, None),
+            eth: AssetType::new("ETH", 18, None),
+            issuer: Wallet::new("Issuer"),
+            issuer_key,
+            alice: Wallet::new("Alice"),
+            bob: Wallet::new("Bob"),
+        }
+    }
+
+    // チェーンの手数料の規則。規則のないチェーンでは使わない
+    pub fn fee_rule(&self) -> FeeRule {
+        *self.chain.ledger().fee_rule().unwrap()
+    }
+
+    // 発行者が outputs の受取人へミントするトランザクション。資産タイプごとに出力の合計だけミントする
+    pub fn mint(&self, outputs: &[(PaymentAddress, AssetType, u64)]) -> Transaction {
+        let mut totals: BTreeMap<AssetType, u64> = BTreeMap::new();
+        let mut builder = TransactionBuilder::new(&self.issuer);
+        for &(recipient, asset_type, amount) in outputs {
+            *totals.entry(asset_type).or_default() += amount;
+            builder = builder.add_output(recipient, asset_type, amount);
+        }
+        for (asset_type, amount) in totals {
+            builder = builder.mint(&self.issuer_key, asset_type, amount);
+        }
+        builder.build(self.chain.ledger().proof_system()).unwrap()
+    }
+
+    // 発行者、Alice、Bobのウォレットをチェーンの先頭のブロックまで同期する
+    pub fn sync(&mut self) {
+        for wallet in [&mut self.issuer, &mut self.alice, &mut self.bob] {
+            for block in &self.chain.blocks()[wallet.height() as usize..] {
+                wallet.sync(block).unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use masp_simulation::asset::AssetType;
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::encoding::{self, VERSION};
+use masp_simulation::error::{DecodeError, ValidationError};
+use masp_simulation::fee::FeeRule;
+use masp_simulation::group::Point;
+use masp_simulation::ledger::{Ledger, LedgerSnapshot};
+use masp_simulation::note::Note;
+use masp_simulation::proof::MockProver;
+use masp_simulation::signature::Signature;
+use masp_simulation::transaction::Transaction;
+use masp_simulation::wallet::Wallet;
+
+use common::{Setup, COIN, PRODUCER};
+
+// Aliceが10 BTCのノートを持ち、Bobの透明なアカウントに5 BTCがあるチェーン。手数料はBTCで払う。
+// 登録簿にはエポック付きの資産も含める
+fn setup() -> Setup {
+    let (mut registry, issuer_key) = common::registry();
+    registry.register("ETH", 18, Some(1));
+    let btc = AssetType::new("BTC", 8, None);
+    let ledger = Ledger::new(registry, MockProver::setup()).with_fee_rule(FeeRule::new(btc, 0, 0));
+    let mut setup = Setup::from_ledger(ledger, issuer_key);
+    let issuance = TransactionBuilder::new(&setup.issuer)
+        .mint(&setup.issuer_key, btc, 15 * COIN)
+        .add_output(setup.alice.address(), btc, 10 * COIN)
+        .add_transparent_output(setup.bob.transparent_address(), btc, 5 * COIN)
+        .build(setup.chain.ledger().proof_system())
+        .unwrap();
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    setup
+}
+
+impl Setup {
+    // Aliceがノートを消費してBobへ送り、一部をバーンし、手数料を払うトランザクション
+    fn payment(&self) -> Transaction {
+        TransactionBuilder::new(&self.alice)
+            .add_output(self.bob.address(), self.btc, 3 * COIN)
+            .add_transparent_output(self.bob.transparent_address(), self.btc, COIN)
+            .burn(self.btc, COIN)
+            .with_fee_rule(self.fee_rule())
+            .with_fee(1000)
+            .build(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+}
+
+#[test]
+fn note_round_trips() {
+    let btc = setup().btc;
+    let note = Note::new(btc, 42, Wallet::new("Carol").address());
+    let bytes = encoding::encode(&note);
+    assert_eq!(bytes[0], VERSION);
+
+    let decoded: Note = encoding::decode(&bytes).unwrap();
+    assert_eq!(decoded.asset_type, note.asset_type);
+    assert_eq!(decoded.amount, note.amount);
+    assert_eq!(decoded.recipient, note.recipient);
+    assert_eq!(decoded.rcm, note.rcm);
+    assert_eq!(encoding::encode(&decoded), bytes);
+}
+
+#[test]
+fn transaction_round_trips_and_stays_valid() {
+    let mut setup = setup();
+    let transaction = setup.payment();
+    let bytes = encoding::encode(&transaction);
+
+    let decoded: Transaction = encoding::decode(&bytes).unwrap();
+    assert_eq!(encoding::encode(&decoded), bytes);
+    assert_eq!(decoded.txid(), transaction.txid());
+    assert_eq!(decoded.sighash(), transaction.sighash());
+    // 復号したトランザクションは証明と署名を保ったまま検証を通る
+    setup.chain.produce_block(vec![decoded], PRODUCER).unwrap();
+}
+
+#[test]
+fn txid_covers_signatures_but_sighash_does_not() {
+    let setup = setup();
+    let transaction = setup.payment();
+    let mut resigned = transaction.clone();
+    resigned.binding_sig = Signature::EMPTY;
+    assert_eq!(resigned.sighash(), transaction.sighash());
+    assert_ne!(resigned.txid(), transaction.txid());
+
+    // 公開される値を変えると、どちらも変わる
+    let mut modified = transaction.clone();
+    *modified.burns.get_mut(&setup.btc).unwrap() += 1;
+    assert_ne!(modified.sighash(), transaction.sighash());
+    assert_ne!(modified.txid(), transaction.txid());
+}
+
+#[test]
+fn ledger_snapshot_round_trips() {
+    let mut setup = setup();
+    let btc = setup.btc;
+    let payment = setup.payment();
+    setup
+        .chain
+        .produce_block(vec![payment.clone()], PRODUCER)
+        .unwrap();
+    let ledger = setup.chain.ledger();
+    let bytes = encoding::encode(&ledger.snapshot());
+
+    let snapshot: LedgerSnapshot = encoding::decode(&bytes).unwrap();
+    let mut restored = Ledger::restore(snapshot, ledger.proof_system().clone());
+    assert_eq!(encoding::encode(&restored.snapshot()), bytes);
+    assert_eq!(restored.tree().root(), ledger.tree().root());
+    assert_eq!(restored.supply(&btc), ledger.supply(&btc));
+    assert_eq!(
+        restored.registry().issuer(&btc),
+        ledger.registry().issuer(&btc)
+    );
+
+    // 復元前に消費されたノートはヌリファイアが残っているので再び消費できない。
+    // 復元した台帳は最終状態より前のアンカーを知らないので、ヌリファイアの集合を直接確かめる
+    assert!(payment
+        .inputs
+        .iter()
+        .all(|input| restored.nullifiers().contains(&input.nullifier)));
+    assert_eq!(
+        restored.apply(&payment),
+        Err(ValidationError::BadAnchor(payment.anchor))
+    );
+    // ウォレットは台帳とは別に同期するので、復元した台帳にもそのままトランザクションを適用できる
+    let bob = &mut setup.bob;
+    bob.sync(setup.chain.blocks().last().unwrap()).unwrap();
+    let transfer = TransactionBuilder::new(bob)
+        .add_output(bob.address(), btc, COIN)
+        .build(restored.proof_system())
+        .unwrap();
+    restored.apply(&transfer).unwrap();
+    let viewing_keys = [setup.alice.full_viewing_key(), bob.full_viewing_key()];
+    assert_eq!(restored.supply_mismatch(&viewing_keys), None);
+}
+
+#[test]
+fn rejects_malformed_envelopes() {
+    let btc = setup().btc;
+    let bytes = encoding::encode(&Note::new(btc, 42, Wallet::new("Carol").address()));
+
+    let mut versioned = bytes.clone();
+    versioned[0] = VERSION + 1;
+    assert_eq!(
+        encoding::decode::<Note>(&versioned).unwrap_err(),
+        DecodeError::UnsupportedVersion(VERSION + 1)
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        encoding::decode::<Note>(&trailing).unwrap_err(),
+        DecodeError::TrailingBytes(1)
+    );
+
+    assert_eq!(
+        encoding::decode::<Note>(&bytes[..bytes.len() - 1]).unwrap_err(),
+        DecodeError::UnexpectedEnd
+    );
+}
+
+#[test]
+fn rejects_non_canonical_values() {
+    let btc = setup().btc;
+    let bytes = encoding::encode(&Note::new(btc, 42, Wallet::new("Carol").address()));
+    // バージョン (1) || 資産タイプ (32) || 量 (8) || ダイバーシファイア (11) || pk_d (32) || rcm (32)
+    let pk_d = 52..84;
+    let rcm = 84..116;
+
+    // y 座標が p 以上の点の表現
+    let mut point = bytes.clone();
+    point[pk_d.clone()].copy_from_slice(&[[0xff; 31].as_slice(), &[0x7f]].concat());
+    assert_eq!(
+        encoding::decode::<Note>(&point).unwrap_err(),
+        DecodeError::InvalidPoint
+    );
+
+    // 位数2の点と、素数位数の点に位数2の点を足した点はどちらも部分群に含まれない
+    let order_two = [[0xec].as_slice(), &[0xff; 30], &[0x7f]].concat();
+    let mut small = bytes.clone();
+    small[pk_d.clone()].copy_from_slice(&order_two);
+    assert_eq!(
+        encoding::decode::<Note>(&small).unwrap_err(),
+        DecodeError::TorsionPoint
+    );
+    let order_two = Point::from_bytes(&order_two.try_into().unwrap()).unwrap();
+    let pk_d_point = Point::from_bytes(&bytes[pk_d.clone()].try_into().unwrap()).unwrap();
+    let mut mixed = bytes.clone();
+    mixed[pk_d.clone()].copy_from_slice(&(pk_d_point + order_two).to_bytes());
+    assert_eq!(
+        encoding::decode::<Note>(&mixed).unwrap_err(),
+        DecodeError::TorsionPoint
+    );
+
+    // 群の位数以上のスカラー
+    let mut scalar = bytes.clone();
+    scalar[rcm].copy_from_slice(&[0xff; 32]);
+    assert_eq!(
+        encoding::decode::<Note>(&scalar).unwrap_err(),
+        DecodeError::NonCanonicalScalar
+    );
+}
+
+#[test]
+fn rejects_unordered_or_duplicate_entries() {
+    let mut burns = BTreeMap::new();
+    burns.insert(AssetType::new("BTC", 8, None), 1u64);
+    burns.insert(AssetType::new("ETH", 18, None), 2u64);
+    let bytes = encoding::encode(&burns);
+    // バージョン (1) || 要素数 (4) || 資産タイプ (32) と量 (8) の組が2つ
+    let (first, second) = (5..45, 45..85);
+
+    let mut swapped = bytes.clone();
+    swapped[first.clone()].copy_from_slice(&bytes[second.clone()]);
+    swapped[second.clone()].copy_from_slice(&bytes[first.clone()]);
+    assert_eq!(
+        encoding::decode::<BTreeMap<AssetType, u64>>(&swapped).unwrap_err(),
+        DecodeError::UnorderedEntries
+    );
+
+    let mut duplicate = bytes.clone();
+    duplicate[second].copy_from_slice(&bytes[first]);
+    assert_eq!(
+        encoding::decode::<BTreeMap<AssetType, u64>>(&duplicate).unwrap_err(),
+        DecodeError::UnorderedEntries
+    );
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use masp_simulation::asset::AssetType;
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::error::{BuildError, ValidationError};
+use masp_simulation::ledger::Ledger;
+use masp_simulation::transaction::Transaction;
+use masp_simulation::wallet::Wallet;
+
+use common::{Setup, COIN, PRODUCER};
+
+// AliceがBTCとETHのノートを1つずつ持ち、BTCで1000 + 1アクションあたり500の手数料を払うチェーン。
+// 発行者は多めにミントしたBTCから手数料を払い、お釣りを受け取る
+fn setup() -> Setup {
+    let mut setup = Setup::with_fee_rule(1000, 500);
+    let issuance = TransactionBuilder::new(&setup.issuer)
+        .mint(&setup.issuer_key, setup.btc, 6 * COIN)
+        .mint(&setup.issuer_key, setup.eth, 5 * COIN)
+        .add_output(setup.alice.address(), setup.btc, 5 * COIN)
+        .add_output(setup.alice.address(), setup.eth, 5 * COIN)
+        .with_fee_rule(setup.fee_rule())
+        .build(setup.chain.ledger().proof_system())
+        .unwrap();
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    setup
+}
+
+impl Setup {
+    // AliceからBobへ amount の asset_type を送り、手数料は規則から見積もる
+    fn alice_pays_bob(&self, asset_type: AssetType, amount: u64) -> Transaction {
+        TransactionBuilder::new(&self.alice)
+            .add_output(self.bob.address(), asset_type, amount)
+            .with_fee_rule(self.fee_rule())
+            .build(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+}
+
+#[test]
+fn estimated_fees_cover_every_action() {
+    let setup = setup();
+    let ledger = setup.chain.ledger();
+
+    // ETHを送っても手数料はBTCで払うので、ETHとBTCのノートを1つずつ消費し、それぞれにお釣りが出る
+    let builder = TransactionBuilder::new(&setup.alice)
+        .add_output(setup.bob.address(), setup.eth, COIN)
+        .with_fee_rule(setup.fee_rule());
+    assert_eq!(
+        builder.estimate_fee().unwrap(),
+        Some((setup.btc, setup.fee_rule().minimum(5)))
+    );
+
+    let transaction = builder.build(ledger.proof_system()).unwrap();
+    assert_eq!(transaction.actions(), 5);
+    assert_eq!(transaction.fee, setup.fee_rule().minimum_for(&transaction));
+    ledger.validate(&transaction).unwrap();
+}
+
+#[test]
+fn rejects_fees_below_the_minimum() {
+    let setup = setup();
+    let ledger = setup.chain.ledger();
+    let transaction = TransactionBuilder::new(&setup.alice)
+        .add_output(setup.bob.address(), setup.btc, COIN)
+        .with_fee_rule(setup.fee_rule())
+        .with_fee(1000)
+        .build(ledger.proof_system())
+        .unwrap();
+    assert_eq!(
+        ledger.validate(&transaction),
+        Err(ValidationError::InsufficientFee {
+            required: setup.fee_rule().minimum(3),
+            paid: 1000,
+        })
+    );
+}
+
+#[test]
+fn fees_are_paid_in_the_fee_asset() {
+    let setup = setup();
+    let ledger = setup.chain.ledger();
+    let fee = setup.fee_rule().minimum(5);
+    // ETHを送るときも、指定した量の手数料は規則の資産タイプであるBTCのノートから払う
+    let transaction = TransactionBuilder::new(&setup.alice)
+        .add_output(setup.bob.address(), setup.eth, COIN)
+        .with_fee_rule(setup.fee_rule())
+        .with_fee(fee)
+        .build(ledger.proof_system())
+        .unwrap();
+    assert_eq!(transaction.fee, fee);
+    assert_eq!(
+        transaction.value_balance.get(&setup.btc),
+        Some(&(fee as i64))
+    );
+    ledger.validate(&transaction).unwrap();
+
+    // 規則がなければ、手数料をどの資産タイプで払うか決まらないので組み立てない
+    assert_eq!(
+        TransactionBuilder::new(&setup.alice)
+            .add_output(setup.bob.address(), setup.eth, COIN)
+            .with_fee(fee)
+            .build(ledger.proof_system())
+            .unwrap_err(),
+        BuildError::NoFeeRule
+    );
+
+    // 同じ状態でも手数料の規則がない台帳では、手数料を払うトランザクションを受け付けない
+    let mut snapshot = ledger.snapshot();
+    snapshot.fee_rule = None;
+    let free = Ledger::restore(snapshot, ledger.proof_system().clone());
+    assert_eq!(
+        free.validate(&setup.alice_pays_bob(setup.btc, COIN)),
+        Err(ValidationError::NoFeeAsset)
+    );
+}
+
+#[test]
+fn block_producers_collect_fees_without_changing_supply() {
+    let mut setup = setup();
+    let payment = setup.alice_pays_bob(setup.btc, COIN);
+    let fee = payment.fee;
+    let supply = setup.chain.ledger().supply(&setup.btc);
+    let collected = setup
+        .chain
+        .ledger()
+        .transparent()
+        .balance(&PRODUCER, &setup.btc);
+
+    setup.chain.produce_block(vec![payment], PRODUCER).unwrap();
+    let ledger = setup.chain.ledger();
+    assert_eq!(
+        ledger.transparent().balance(&PRODUCER, &setup.btc),
+        collected + fee
+    );
+    assert_eq!(ledger.unclaimed_fees(), 0);
+    assert_eq!(ledger.supply(&setup.btc), supply);
+    // ノートは各ウォレットの完全閲覧鍵で復号して数える
+    let viewing_keys = [&setup.issuer, &setup.alice, &setup.bob].map(Wallet::full_viewing_key);
+    assert_eq!(ledger.supply_mismatch(&viewing_keys), None);
+}
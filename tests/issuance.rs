@@ -0,0 +1,125 @@
+mod common;
+
+use masp_simulation::asset::AssetType;
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::error::{BuildError, ValidationError};
+use masp_simulation::issuance::IssuanceKey;
+use masp_simulation::ledger::Ledger;
+use masp_simulation::proof::MockProver;
+use masp_simulation::transaction::UnauthorizedTransaction;
+
+use common::{Setup, COIN, PRODUCER};
+
+// 発行者がAliceへ5 BTCをミントしたチェーンと、発行者の鍵が登録されていない資産
+fn setup() -> (Setup, AssetType) {
+    let (mut registry, issuer_key) = common::registry();
+    let dai = registry.register("DAI", 18, None);
+    let mut setup = Setup::from_ledger(Ledger::new(registry, MockProver::setup()), issuer_key);
+    let issuance = setup.mint(&[(setup.alice.address(), setup.btc, 5 * COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    (setup, dai)
+}
+
+#[test]
+fn only_registered_issuers_can_mint() {
+    let (setup, dai) = setup();
+    let ledger = setup.chain.ledger();
+    let impostor = IssuanceKey::random();
+
+    // 登録された発行者以外の鍵によるミント
+    let forged = TransactionBuilder::new(&setup.issuer)
+        .mint(&impostor, setup.btc, COIN)
+        .add_output(setup.alice.address(), setup.btc, COIN)
+        .build(ledger.proof_system())
+        .unwrap();
+    assert_eq!(
+        ledger.validate(&forged),
+        Err(ValidationError::UnauthorizedIssuance(setup.btc))
+    );
+
+    // 発行者が登録されていない資産は誰もミントできない
+    let unregistered = TransactionBuilder::new(&setup.issuer)
+        .mint(&setup.issuer_key, dai, COIN)
+        .add_output(setup.alice.address(), dai, COIN)
+        .build(ledger.proof_system())
+        .unwrap();
+    assert_eq!(
+        ledger.validate(&unregistered),
+        Err(ValidationError::UnauthorizedIssuance(dai))
+    );
+
+    // 発行者の公開鍵を名乗っても、その鍵による署名がなければミントできない
+    let mut unsigned = TransactionBuilder::new(&setup.issuer)
+        .mint(&setup.issuer_key, setup.btc, COIN)
+        .add_output(setup.alice.address(), setup.btc, COIN)
+        .build(ledger.proof_system())
+        .unwrap();
+    ledger.validate(&unsigned).unwrap();
+    unsigned.issuance[0].signature = impostor.sign(&unsigned.sighash());
+    assert_eq!(
+        ledger.validate(&unsigned),
+        Err(ValidationError::UnauthorizedIssuance(setup.btc))
+    );
+    assert_eq!(ledger.supply(&setup.btc), 5 * COIN);
+}
+
+#[test]
+fn cannot_burn_more_than_the_supply() {
+    let (setup, _) = setup();
+    let ledger = setup.chain.ledger();
+    // シールドされたプールから6 BTCが出ていくと主張し、3 BTCずつ2回に分けて破棄する。
+    // 収支は合っているが、総供給量の5 BTCを超えて破棄することになる
+    let transaction = UnauthorizedTransaction::new(setup.alice.anchor(), Vec::new(), Vec::new())
+        .with_value_balance(setup.btc, 6 * COIN as i64)
+        .with_burn(setup.btc, 3 * COIN)
+        .unwrap()
+        .with_burn(setup.btc, 3 * COIN)
+        .unwrap();
+    let transaction = transaction.prove(ledger.proof_system()).unwrap();
+    assert_eq!(transaction.burns[&setup.btc], 6 * COIN);
+    assert_eq!(
+        ledger.validate(&transaction),
+        Err(ValidationError::BurnExceedsSupply(setup.btc))
+    );
+}
+
+#[test]
+fn burns_that_overflow_are_rejected() {
+    let (setup, _) = setup();
+    let transaction = UnauthorizedTransaction::new(setup.alice.anchor(), Vec::new(), Vec::new())
+        .with_burn(setup.btc, u64::MAX)
+        .unwrap()
+        .with_burn(setup.btc, 1);
+    assert_eq!(
+        transaction.err(),
+        Some(BuildError::AmountOverflow(setup.btc))
+    );
+
+    let built = TransactionBuilder::new(&setup.alice)
+        .burn(setup.btc, u64::MAX)
+        .burn(setup.btc, 1)
+        .build(setup.chain.ledger().proof_system());
+    assert_eq!(built.err(), Some(BuildError::AmountOverflow(setup.btc)));
+}
+
+#[test]
+fn burns_of_the_same_asset_add_up() {
+    let (mut setup, _) = setup();
+    let transaction = TransactionBuilder::new(&setup.alice)
+        .burn(setup.btc, COIN)
+        .burn(setup.btc, 2 * COIN)
+        .build(setup.chain.ledger().proof_system())
+        .unwrap();
+    assert_eq!(transaction.burns[&setup.btc], 3 * COIN);
+
+    let block = setup
+        .chain
+        .produce_block(vec![transaction], PRODUCER)
+        .unwrap();
+    setup.alice.sync(block).unwrap();
+    assert_eq!(setup.chain.ledger().supply(&setup.btc), 2 * COIN);
+    assert_eq!(setup.alice.balance(&setup.btc).unwrap().spendable, 2 * COIN);
+    let viewing_keys = [setup.alice.full_viewing_key()];
+    assert_eq!(setup.chain.ledger().supply_mismatch(&viewing_keys), None);
+}
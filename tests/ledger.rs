@@ -0,0 +1,79 @@
+mod common;
+
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::encoding;
+use masp_simulation::error::ValidationError;
+use masp_simulation::ledger::Ledger;
+use masp_simulation::transaction::Transaction;
+
+use common::{Setup, COIN, PRODUCER};
+
+// Aliceが5 BTCを持ち、手数料をBTCで払うチェーン。最低手数料はない
+fn setup() -> Setup {
+    let mut setup = Setup::with_fee_rule(0, 0);
+    let issuance = setup.mint(&[(setup.alice.address(), setup.btc, 5 * COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    setup
+}
+
+impl Setup {
+    // AliceからBobへ2 BTCを送り、手数料 fee を払うトランザクション
+    fn payment(&self, fee: u64) -> Transaction {
+        TransactionBuilder::new(&self.alice)
+            .add_output(self.bob.address(), self.btc, 2 * COIN)
+            .with_fee_rule(self.fee_rule())
+            .with_fee(fee)
+            .build(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+}
+
+// apply が error で失敗し、台帳の状態が符号化したバイト列まで変わらないことを確かめる
+fn assert_apply_fails(ledger: &mut Ledger, transaction: &Transaction, error: ValidationError) {
+    let before = encoding::encode(&ledger.snapshot());
+    let root = ledger.tree().root();
+    assert_eq!(ledger.apply(transaction), Err(error));
+    assert_eq!(encoding::encode(&ledger.snapshot()), before);
+    assert_eq!(ledger.tree().root(), root);
+}
+
+#[test]
+fn applies_valid_transactions() {
+    let setup = setup();
+    let mut ledger = setup.chain.ledger().clone();
+    let payment = setup.payment(10);
+    ledger.apply(&payment).unwrap();
+    assert!(ledger.nullifiers().contains(&payment.inputs[0].nullifier));
+    assert_eq!(ledger.unclaimed_fees(), 10);
+    assert_eq!(ledger.outputs().len(), 3);
+    assert!(ledger.tree().is_known_anchor(&ledger.tree().root()));
+}
+
+#[test]
+fn failed_validation_leaves_the_ledger_unchanged() {
+    let setup = setup();
+    let mut ledger = setup.chain.ledger().clone();
+    let payment = setup.payment(10);
+    ledger.apply(&payment).unwrap();
+
+    // 同じトランザクションをもう一度適用しても、ヌリファイアも出力も増えない
+    assert_apply_fails(
+        &mut ledger,
+        &payment,
+        ValidationError::DoubleSpend(payment.inputs[0].nullifier),
+    );
+}
+
+#[test]
+fn failures_after_validation_leave_the_ledger_unchanged() {
+    let setup = setup();
+    // 預かっている手数料が上限に達していると、検証を通ったトランザクションでも手数料を足せない
+    let mut snapshot = setup.chain.ledger().snapshot();
+    snapshot.unclaimed_fees = u64::MAX;
+    let mut ledger = Ledger::restore(snapshot, setup.chain.ledger().proof_system().clone());
+    let payment = setup.payment(10);
+    ledger.validate(&payment).unwrap();
+    assert_apply_fails(&mut ledger, &payment, ValidationError::FeeOverflow);
+    assert!(!ledger.nullifiers().contains(&payment.inputs[0].nullifier));
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::error::{MempoolError, ValidationError};
+use masp_simulation::mempool::Mempool;
+use masp_simulation::transaction::Transaction;
+use masp_simulation::wallet::Wallet;
+
+use common::{Setup, COIN, PRODUCER};
+
+// AliceとBobがそれぞれ5 BTCのノートを1つずつ持ち、手数料をBTCで払うチェーンと、受け取るだけのCarol。最低手数料はない
+fn setup() -> (Setup, Wallet) {
+    let mut setup = Setup::with_fee_rule(0, 0);
+    let issuance = setup.mint(&[
+        (setup.alice.address(), setup.btc, 5 * COIN),
+        (setup.bob.address(), setup.btc, 5 * COIN),
+    ]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    (setup, Wallet::new("Carol"))
+}
+
+impl Setup {
+    // from から to へ amount を送り、手数料 fee を払うトランザクション
+    fn pay(&self, from: &Wallet, to: &Wallet, amount: u64, fee: u64) -> Transaction {
+        TransactionBuilder::new(from)
+            .add_output(to.address(), self.btc, amount)
+            .with_fee_rule(self.fee_rule())
+            .with_fee(fee)
+            .build(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+}
+
+#[test]
+fn rejects_spends_that_conflict_with_pending_transactions() {
+    let (setup, carol) = setup();
+    let mut mempool = Mempool::new();
+    let to_bob = setup.pay(&setup.alice, &setup.bob, COIN, 10);
+    let to_carol = setup.pay(&setup.alice, &carol, COIN, 20);
+
+    let txid = mempool.insert(&setup.chain, to_bob.clone()).unwrap();
+    assert_eq!(
+        mempool.insert(&setup.chain, to_bob),
+        Err(MempoolError::AlreadyPending(txid))
+    );
+    // 同じノートを消費するので、手数料が高くても受け付けない
+    assert_eq!(
+        mempool.insert(&setup.chain, to_carol.clone()),
+        Err(MempoolError::Conflict(to_carol.inputs[0].nullifier, txid))
+    );
+
+    // 保留中のトランザクションを取り除けば、衝突していたものを受け付けられる
+    mempool.remove(&txid).unwrap();
+    mempool.insert(&setup.chain, to_carol).unwrap();
+    assert_eq!(mempool.len(), 1);
+}
+
+#[test]
+fn rejects_transactions_that_fail_validation() {
+    let (setup, _) = setup();
+    let mut mempool = Mempool::new();
+    let mut forged = setup.pay(&setup.alice, &setup.bob, COIN, 10);
+    forged.outputs.pop();
+    assert_eq!(
+        mempool.insert(&setup.chain, forged.clone()),
+        Err(MempoolError::Invalid(ValidationError::BadSignature(
+            forged.inputs[0].nullifier
+        )))
+    );
+    assert!(mempool.is_empty());
+}
+
+#[test]
+fn builds_blocks_in_fee_order() {
+    let (mut setup, carol) = setup();
+    let mut mempool = Mempool::new();
+    let cheap = mempool
+        .insert(&setup.chain, setup.pay(&setup.alice, &carol, COIN, 10))
+        .unwrap();
+    let expensive = mempool
+        .insert(&setup.chain, setup.pay(&setup.bob, &carol, COIN, 30))
+        .unwrap();
+
+    let block = mempool.build_block(&setup.chain, PRODUCER, 10).unwrap();
+    let txids: Vec<_> = block.transactions.iter().map(Transaction::txid).collect();
+    assert_eq!(txids, vec![expensive, cheap]);
+
+    // 1つしか入らなければ手数料の高いほうを選び、もう一方は次のブロックを待つ
+    let block = mempool.build_block(&setup.chain, PRODUCER, 1).unwrap();
+    assert_eq!(block.transactions[0].txid(), expensive);
+    setup.chain.apply_block(&block).unwrap();
+    assert_eq!(
+        setup
+            .chain
+            .ledger()
+            .transparent()
+            .balance(&PRODUCER, &setup.btc),
+        30
+    );
+    assert!(mempool.prune(&setup.chain, &block).is_empty());
+    assert!(!mempool.contains(&expensive));
+    assert!(mempool.contains(&cheap));
+}
+
+#[test]
+fn evicts_transactions_that_lose_a_race() {
+    let (mut setup, carol) = setup();
+    let mut mempool = Mempool::new();
+    let pending = mempool
+        .insert(&setup.chain, setup.pay(&setup.alice, &setup.bob, COIN, 10))
+        .unwrap();
+
+    // 別のノードが同じノートを消費するトランザクションを先にブロックへ含めた
+    let mut other_node = setup.chain.clone();
+    let winner = setup.pay(&setup.alice, &carol, COIN, 5);
+    let block = other_node
+        .produce_block(vec![winner], PRODUCER)
+        .unwrap()
+        .clone();
+
+    setup.chain.apply_block(&block).unwrap();
+    assert_eq!(mempool.prune(&setup.chain, &block), vec![pending]);
+    assert!(mempool.is_empty());
+}
@@ -0,0 +1,64 @@
+use masp_simulation::commitment::NoteCommitment;
+use masp_simulation::keys::SpendingKey;
+use masp_simulation::nullifier::{Nullifier, NullifierSet};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn derivation_matches_known_answer() {
+    let nk = SpendingKey::from_seed(b"alice").full_viewing_key().nk;
+    let nullifier = Nullifier::derive(&nk, &NoteCommitment([1; 32]), 0);
+    assert_eq!(
+        hex(&nullifier.0),
+        "dce8e8b11f75316f984c953b3390e93b5bc317d98c0bdcdcd0e8e86efbd4d82b"
+    );
+}
+
+#[test]
+fn derivation_depends_on_key_commitment_and_position() {
+    let nk = SpendingKey::from_seed(b"alice").full_viewing_key().nk;
+    let other = SpendingKey::from_seed(b"bob").full_viewing_key().nk;
+    let cm = NoteCommitment([1; 32]);
+    let nullifier = Nullifier::derive(&nk, &cm, 0);
+
+    // 同じノートからは常に同じ値が導出され、鍵、コミットメント、位置のどれが違っても別の値になる
+    assert_eq!(Nullifier::derive(&nk, &cm, 0), nullifier);
+    assert_ne!(Nullifier::derive(&other, &cm, 0), nullifier);
+    assert_ne!(
+        Nullifier::derive(&nk, &NoteCommitment([2; 32]), 0),
+        nullifier
+    );
+    assert_ne!(Nullifier::derive(&nk, &cm, 1), nullifier);
+}
+
+#[test]
+fn set_records_each_nullifier_once() {
+    let first = Nullifier([1; 32]);
+    let second = Nullifier([2; 32]);
+    let mut set = NullifierSet::new();
+    assert!(!set.contains(&first));
+
+    assert!(set.insert(first));
+    assert!(set.contains(&first));
+    assert!(!set.contains(&second));
+    assert!(!set.insert(first));
+    assert_eq!(set.iter().count(), 1);
+}
+
+#[test]
+fn digest_depends_only_on_contents() {
+    let mut forward = NullifierSet::new();
+    let mut backward = NullifierSet::new();
+    let empty = forward.digest();
+    for byte in 1..=3 {
+        forward.insert(Nullifier([byte; 32]));
+        backward.insert(Nullifier([4 - byte; 32]));
+    }
+    assert_eq!(forward.digest(), backward.digest());
+    assert_ne!(forward.digest(), empty);
+
+    backward.insert(Nullifier([4; 32]));
+    assert_ne!(forward.digest(), backward.digest());
+}
@@ -0,0 +1,63 @@
+use masp_simulation::group::{Point, Scalar};
+use masp_simulation::keys::spend_auth_base;
+use masp_simulation::signature::Signature;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(hex: &str) -> Vec<u8> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|chunk| u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn ed25519_points_match_standard_encodings() {
+    // 基点 B と、その2倍と3倍の標準的な圧縮表現
+    let basepoint = Point::from_bytes(
+        &unhex("5866666666666666666666666666666666666666666666666666666666666666")
+            .try_into()
+            .unwrap(),
+    )
+    .unwrap();
+    let double = "c9a3f86aae465f0e56513864510f3997561fa2c9e85ea21dc2292309f3cd6022";
+    let triple = "d4b4f5784868c3020403246717ec169ff79e26608ea126a1ab69ee77d1b16712";
+
+    assert_eq!(hex(&basepoint.double().to_bytes()), double);
+    assert_eq!(hex(&(basepoint + basepoint).to_bytes()), double);
+    assert_eq!(hex(&(basepoint * Scalar::from_u64(3)).to_bytes()), triple);
+    assert_eq!(
+        hex(&(basepoint * Scalar::from_u64(3) - basepoint).to_bytes()),
+        double
+    );
+    assert_eq!(
+        Point::from_bytes(&unhex(triple).try_into().unwrap()).unwrap(),
+        basepoint * Scalar::from_u64(3)
+    );
+    assert_eq!(basepoint * Scalar::ZERO, Point::IDENTITY);
+}
+
+#[test]
+fn signatures_reject_small_order_public_keys() {
+    let base = spend_auth_base();
+    // 空の署名は単位元の公開鍵に対して式を満たすが、検証には通らない
+    assert!(!Signature::EMPTY.verify(base, &Point::IDENTITY, b"message"));
+
+    // 位数2の点を公開鍵にすると、チャレンジが偶数のときは s = 0, R = 単位元 の署名が式を満たしてしまう
+    let order_two = Point::from_bytes(
+        &unhex("ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f")
+            .try_into()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_ne!(order_two, Point::IDENTITY);
+    assert_eq!(order_two.double(), Point::IDENTITY);
+    assert!(!Signature::EMPTY.verify(base, &order_two, b"message"));
+
+    let secret_key = Scalar::from_u64(42);
+    let signature = Signature::sign(base, &secret_key, b"message");
+    assert!(signature.verify(base, &(*base * secret_key), b"message"));
+    assert!(!signature.verify(base, &(*base * secret_key), b"other message"));
+}
@@ -0,0 +1,215 @@
+mod common;
+
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::commitment::{NoteCommitment, ValueCommitment};
+use masp_simulation::error::{BuildError, ValidationError};
+use masp_simulation::group::Scalar;
+use masp_simulation::note::Note;
+use masp_simulation::proof::{
+    MockProver, OutputStatement, OutputWitness, ProofSystem, SpendStatement,
+};
+use masp_simulation::transaction::{Transaction, UnauthorizedTransaction};
+use masp_simulation::tree::Anchor;
+
+use common::{Setup, COIN, PRODUCER};
+
+// Aliceが5 BTCを持つチェーン
+fn setup() -> Setup {
+    let mut setup = Setup::new();
+    let issuance = setup.mint(&[(setup.alice.address(), setup.btc, 5 * COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    setup
+}
+
+impl Setup {
+    // AliceからBobへ2 BTCを送るトランザクション。証明は prover で作る
+    fn payment<P: ProofSystem>(&self, prover: &P) -> Transaction {
+        TransactionBuilder::new(&self.alice)
+            .add_output(self.bob.address(), self.btc, 2 * COIN)
+            .build(prover)
+            .unwrap()
+    }
+
+    // 入力のない、発行者からBobへのミント。出力の証明だけを確かめるのに使う
+    fn mint_to_bob<P: ProofSystem>(&self, prover: &P) -> Transaction {
+        TransactionBuilder::new(&self.issuer)
+            .mint(&self.issuer_key, self.btc, COIN)
+            .add_output(self.bob.address(), self.btc, COIN)
+            .build(prover)
+            .unwrap()
+    }
+}
+
+#[test]
+fn rejects_proofs_from_another_setup() {
+    let setup = setup();
+    let ledger = setup.chain.ledger();
+    // 別のセットアップの証明系で作った証明は、台帳の証明系では検証できない
+    let other = MockProver::setup();
+
+    let payment = setup.payment(&other);
+    assert_eq!(
+        ledger.validate(&payment),
+        Err(ValidationError::InvalidSpendProof(
+            payment.inputs[0].nullifier
+        ))
+    );
+    let mint = setup.mint_to_bob(&other);
+    assert_eq!(
+        ledger.validate(&mint),
+        Err(ValidationError::InvalidOutputProof(
+            mint.outputs[0].encrypted.cm
+        ))
+    );
+
+    // 正しいトランザクションに、別のセットアップの証明だけを差し込んでも同じ
+    let mut payment = setup.payment(ledger.proof_system());
+    ledger.validate(&payment).unwrap();
+    payment.inputs[0].proof = setup.payment(&other).inputs[0].proof.clone();
+    assert_eq!(
+        ledger.validate(&payment),
+        Err(ValidationError::InvalidSpendProof(
+            payment.inputs[0].nullifier
+        ))
+    );
+    let mut mint = setup.mint_to_bob(ledger.proof_system());
+    ledger.validate(&mint).unwrap();
+    mint.outputs[0].proof = setup.mint_to_bob(&other).outputs[0].proof.clone();
+    assert_eq!(
+        ledger.validate(&mint),
+        Err(ValidationError::InvalidOutputProof(
+            mint.outputs[0].encrypted.cm
+        ))
+    );
+}
+
+#[test]
+fn rejects_commitments_changed_after_proving() {
+    let setup = setup();
+    let ledger = setup.chain.ledger();
+    let one = ValueCommitment::public(&setup.btc, 1);
+
+    // 入力の値コミットメントを、証明を作ったあとで1だけ増やす
+    let mut payment = setup.payment(ledger.proof_system());
+    payment.inputs[0].cv = ValueCommitment(payment.inputs[0].cv.0 + one.0);
+    assert_eq!(
+        ledger.validate(&payment),
+        Err(ValidationError::InvalidSpendProof(
+            payment.inputs[0].nullifier
+        ))
+    );
+
+    // 出力の値コミットメントとノートコミットメントも、証明を作ったあとでは変えられない
+    let mut mint = setup.mint_to_bob(ledger.proof_system());
+    mint.outputs[0].cv = ValueCommitment(mint.outputs[0].cv.0 + one.0);
+    assert_eq!(
+        ledger.validate(&mint),
+        Err(ValidationError::InvalidOutputProof(
+            mint.outputs[0].encrypted.cm
+        ))
+    );
+    let mut mint = setup.mint_to_bob(ledger.proof_system());
+    mint.outputs[0].encrypted.cm = NoteCommitment([1; 32]);
+    assert_eq!(
+        ledger.validate(&mint),
+        Err(ValidationError::InvalidOutputProof(NoteCommitment([1; 32])))
+    );
+}
+
+#[test]
+fn refuses_witnesses_that_do_not_satisfy_the_statement() {
+    let setup = setup();
+    let prover = setup.chain.ledger().proof_system();
+    let received = setup.alice.unspent_notes().next().unwrap();
+    let witness = setup.alice.spend(received).witness;
+    let statement = SpendStatement {
+        anchor: setup.alice.anchor(),
+        cv: ValueCommitment::derive(&setup.btc, witness.note.amount, &witness.rcv),
+        nullifier: received.nullifier,
+        rk: witness.fvk.randomized_ak(&witness.alpha),
+    };
+    assert!(prover.prove(&statement, &witness).is_some());
+
+    // 認証パスが、ノートからアンカーへつながらない
+    let mut wrong_path = witness.clone();
+    wrong_path.path.auth_path[0] = [1; 32];
+    assert!(prover.prove(&statement, &wrong_path).is_none());
+    // アンカーが、認証パスから計算したルートと異なる
+    let wrong_anchor = SpendStatement {
+        anchor: Anchor([1; 32]),
+        ..statement.clone()
+    };
+    assert!(prover.prove(&wrong_anchor, &witness).is_none());
+    // 値コミットメントが、ノートの量と一致しない
+    let wrong_cv = SpendStatement {
+        cv: ValueCommitment::derive(&setup.btc, witness.note.amount + 1, &witness.rcv),
+        ..statement
+    };
+    assert!(prover.prove(&wrong_cv, &witness).is_none());
+
+    let note = Note::new(setup.btc, COIN, setup.bob.address());
+    let rcv = Scalar::random();
+    let output = OutputStatement {
+        cm: note.commit(),
+        cv: ValueCommitment::derive(&setup.btc, COIN, &rcv),
+    };
+    let output_witness = OutputWitness { note, rcv };
+    assert!(prover.prove(&output, &output_witness).is_some());
+    let wrong_cv = OutputStatement {
+        cv: ValueCommitment::derive(&setup.btc, 2 * COIN, &rcv),
+        ..output
+    };
+    assert!(prover.prove(&wrong_cv, &output_witness).is_none());
+}
+
+#[test]
+fn building_fails_without_a_satisfying_witness() {
+    let setup = setup();
+    let received = setup.alice.unspent_notes().next().unwrap();
+    // 入力の認証パスはアンカー [1; 32] につながらないので、証明を作れない
+    let transaction = UnauthorizedTransaction::new(
+        Anchor([1; 32]),
+        vec![setup.alice.spend(received)],
+        vec![Note::new(setup.btc, 5 * COIN, setup.bob.address())],
+    );
+    assert_eq!(
+        transaction.prove(setup.chain.ledger().proof_system()).err(),
+        Some(BuildError::ProofFailed)
+    );
+}
+
+#[test]
+fn rejects_stale_proofs() {
+    let mut setup = setup();
+    let payment = setup.payment(setup.chain.ledger().proof_system());
+    let issuance = setup.mint(&[(setup.bob.address(), setup.btc, COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    let ledger = setup.chain.ledger();
+    let root = ledger.tree().root();
+    assert_ne!(payment.anchor, root);
+    // 以前のアンカーに対する証明は、そのアンカーが知られている限り有効
+    ledger.validate(&payment).unwrap();
+
+    // 証明を作ったあとでアンカーを新しいルートに差し替えると、証明は古いアンカーのまま
+    let mut moved = payment.clone();
+    moved.anchor = root;
+    assert_eq!(
+        ledger.validate(&moved),
+        Err(ValidationError::InvalidSpendProof(
+            moved.inputs[0].nullifier
+        ))
+    );
+
+    // 同期していないウォレットの認証パスでは、新しいルートに対する証明を作れない
+    let received = setup.alice.unspent_notes().next().unwrap();
+    let stale = UnauthorizedTransaction::new(
+        root,
+        vec![setup.alice.spend(received)],
+        vec![Note::new(setup.btc, 5 * COIN, setup.bob.address())],
+    );
+    assert_eq!(
+        stale.prove(ledger.proof_system()).err(),
+        Some(BuildError::ProofFailed)
+    );
+}
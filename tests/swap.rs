@@ -0,0 +1,179 @@
+mod common;
+
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::error::{BlockError, BuildError, ValidationError};
+use masp_simulation::note::Note;
+use masp_simulation::transaction::{Transaction, UnauthorizedTransaction};
+use masp_simulation::wallet::Wallet;
+
+use common::{Setup, COIN, PRODUCER};
+
+// AliceがBTCを、BobがETHを持つチェーン
+fn setup() -> Setup {
+    setup_with_names("Alice", "Bob")
+}
+
+// setup と同じチェーンで、ウォレットの表示名だけを指定する
+fn setup_with_names(alice: &str, bob: &str) -> Setup {
+    let mut setup = Setup::new();
+    setup.alice = Wallet::new(alice);
+    setup.bob = Wallet::new(bob);
+    let issuance = setup.mint(&[
+        (setup.alice.address(), setup.btc, 2 * COIN),
+        (setup.bob.address(), setup.eth, 30 * COIN),
+    ]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    setup
+}
+
+impl Setup {
+    // AliceからBobへの1 BTCと、BobからAliceへの20 ETHを1つのトランザクションで交換する
+    fn swap(&self) -> Result<Transaction, BuildError> {
+        TransactionBuilder::new(&self.alice)
+            .add_output(self.bob.address(), self.btc, COIN)
+            .add_output_from(&self.bob, self.alice.address(), self.eth, 20 * COIN)
+            .build(self.chain.ledger().proof_system())
+    }
+}
+
+#[test]
+fn swaps_two_assets_atomically() {
+    let mut setup = setup();
+    let transaction = setup.swap().unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+
+    let block = setup
+        .chain
+        .produce_block(vec![transaction], PRODUCER)
+        .unwrap();
+    setup.alice.sync(block).unwrap();
+    setup.bob.sync(block).unwrap();
+
+    let (btc, eth) = (setup.btc, setup.eth);
+    assert_eq!(setup.alice.balance(&btc).unwrap().spendable, COIN);
+    assert_eq!(setup.alice.balance(&eth).unwrap().spendable, 20 * COIN);
+    assert_eq!(setup.bob.balance(&btc).unwrap().spendable, COIN);
+    assert_eq!(setup.bob.balance(&eth).unwrap().spendable, 10 * COIN);
+    // 消費したノートは消費済みとしてウォレットに残る
+    assert!(setup.alice.notes().iter().any(|received| received.spent));
+    let viewing_keys = [setup.alice.full_viewing_key(), setup.bob.full_viewing_key()];
+    assert_eq!(setup.chain.ledger().supply_mismatch(&viewing_keys), None);
+}
+
+#[test]
+fn signs_inputs_by_key_rather_than_by_name() {
+    // 表示名が同じでも、各入力にはその入力の鍵を持つウォレットだけが署名する
+    let setup = setup_with_names("user", "user");
+    let (alice, bob) = (&setup.alice, &setup.bob);
+    let mut transaction = UnauthorizedTransaction::new(
+        alice.anchor(),
+        vec![alice.spend(&alice.notes()[0]), bob.spend(&bob.notes()[0])],
+        vec![
+            Note::new(setup.btc, 2 * COIN, bob.address()),
+            Note::new(setup.eth, 30 * COIN, alice.address()),
+        ],
+    );
+    alice.authorize(&mut transaction);
+    bob.authorize(&mut transaction);
+    let transaction = transaction
+        .prove(setup.chain.ledger().proof_system())
+        .unwrap();
+    assert_eq!(setup.chain.ledger().validate(&transaction), Ok(()));
+}
+
+#[test]
+fn payers_are_told_apart_even_with_the_same_name() {
+    // Bobの表示名がAliceと同じでも、Bobから支払う出力はBobのノートでまかなう
+    let mut setup = setup_with_names("user", "user");
+    let transaction = setup.swap().unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+
+    let block = setup
+        .chain
+        .produce_block(vec![transaction], PRODUCER)
+        .unwrap();
+    setup.alice.sync(block).unwrap();
+    setup.bob.sync(block).unwrap();
+    let (btc, eth) = (setup.btc, setup.eth);
+    assert_eq!(setup.alice.balance(&btc).unwrap().spendable, COIN);
+    assert_eq!(setup.alice.balance(&eth).unwrap().spendable, 20 * COIN);
+    assert_eq!(setup.bob.balance(&btc).unwrap().spendable, COIN);
+    assert_eq!(setup.bob.balance(&eth).unwrap().spendable, 10 * COIN);
+}
+
+#[test]
+fn rejected_swap_leaves_the_chain_unchanged() {
+    let mut setup = setup();
+    let mut transaction = setup.swap().unwrap();
+    // 署名のあとで出力を取り除くとシグハッシュが変わり、どちらの入力の署名も無効になる
+    transaction.outputs.pop();
+
+    let root = setup.chain.ledger().tree().root();
+    assert_eq!(
+        setup
+            .chain
+            .produce_block(vec![transaction.clone()], PRODUCER)
+            .err(),
+        Some(BlockError::InvalidTransaction(
+            0,
+            ValidationError::BadSignature(transaction.inputs[0].nullifier)
+        ))
+    );
+    assert_eq!(setup.chain.ledger().tree().root(), root);
+    assert!(transaction.inputs.iter().all(|input| !setup
+        .chain
+        .ledger()
+        .nullifiers()
+        .contains(&input.nullifier)));
+    assert_eq!(setup.alice.balance(&setup.btc).unwrap().spendable, 2 * COIN);
+    assert_eq!(setup.bob.balance(&setup.eth).unwrap().spendable, 30 * COIN);
+}
+
+#[test]
+fn payers_must_be_synced_to_the_same_block() {
+    let mut setup = setup();
+    // Aliceは自分宛てにBTCを送り、そのブロックまで同期する
+    let transfer = TransactionBuilder::new(&setup.alice)
+        .add_output(setup.alice.address(), setup.btc, COIN)
+        .build(setup.chain.ledger().proof_system())
+        .unwrap();
+    let block = setup.chain.produce_block(vec![transfer], PRODUCER).unwrap();
+    setup.alice.sync(block).unwrap();
+
+    // Bobは1つ前のブロックまでしか同期していないので、Bobのノートの認証パスはAliceのアンカーに対して作れない
+    assert_eq!(
+        setup.swap().unwrap_err(),
+        BuildError::UnsyncedPayer("Bob".to_string())
+    );
+    setup
+        .bob
+        .sync(setup.chain.blocks().last().unwrap())
+        .unwrap();
+    setup.swap().unwrap();
+}
+
+#[test]
+fn balances_each_asset_separately() {
+    let setup = setup();
+    let alice = &setup.alice;
+    // 2 BTCの入力に対して2 ETHの出力。量の合計は一致するが資産タイプごとには合わない
+    let mut transaction = UnauthorizedTransaction::new(
+        alice.anchor(),
+        vec![alice.spend(&alice.notes()[0])],
+        vec![Note::new(setup.eth, 2 * COIN, alice.address())],
+    );
+    // どちらの資産タイプも合わないので、識別子の小さいほうが返る
+    assert_eq!(
+        transaction.value_imbalance(),
+        Some(setup.btc.min(setup.eth))
+    );
+    alice.authorize(&mut transaction);
+    let transaction = transaction
+        .prove(setup.chain.ledger().proof_system())
+        .unwrap();
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::BadBindingSignature)
+    );
+}
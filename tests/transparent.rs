@@ -0,0 +1,169 @@
+mod common;
+
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::encoding;
+use masp_simulation::error::{BlockError, BuildError, ValidationError};
+use masp_simulation::group::Scalar;
+use masp_simulation::keys::spend_auth_base;
+use masp_simulation::signature::Signature;
+use masp_simulation::transaction::Transaction;
+use masp_simulation::transparent::TransparentKey;
+
+use common::{Setup, COIN, PRODUCER};
+
+// Aliceが5 BTCのノートのうち3 BTCを自分の透明なアカウントへ引き出したチェーン
+fn setup() -> Setup {
+    let mut setup = Setup::new();
+    let issuance = setup.mint(&[(setup.alice.address(), setup.btc, 5 * COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+
+    let unshield = setup.unshield(3 * COIN).unwrap();
+    setup.submit(unshield).unwrap();
+    setup
+}
+
+impl Setup {
+    // Aliceのノートから自分の透明なアカウントへ amount を引き出す
+    fn unshield(&self, amount: u64) -> Result<Transaction, BuildError> {
+        TransactionBuilder::new(&self.alice)
+            .add_transparent_output(self.alice.transparent_address(), self.btc, amount)
+            .build(self.chain.ledger().proof_system())
+    }
+
+    // Aliceの透明なアカウントから、現在のノンスで amount をノートへ戻す
+    fn shield(&self, amount: u64) -> Transaction {
+        let nonce = self
+            .chain
+            .ledger()
+            .transparent()
+            .nonce(&self.alice.transparent_address());
+        TransactionBuilder::new(&self.alice)
+            .add_transparent_input(self.btc, amount, nonce)
+            .build(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+
+    // トランザクションを1つだけ含むブロックを作り、Aliceを同期する
+    fn submit(&mut self, transaction: Transaction) -> Result<(), BlockError> {
+        let block = self.chain.produce_block(vec![transaction], PRODUCER)?;
+        self.alice.sync(block).unwrap();
+        Ok(())
+    }
+
+    // トランザクションが error で拒否され、台帳が変わらないことを確かめる
+    fn assert_rejected(&mut self, transaction: Transaction, error: ValidationError) {
+        let before = encoding::encode(&self.chain.ledger().snapshot());
+        assert_eq!(
+            self.submit(transaction),
+            Err(BlockError::InvalidTransaction(0, error))
+        );
+        assert_eq!(encoding::encode(&self.chain.ledger().snapshot()), before);
+    }
+
+    fn transparent_balance(&self) -> u64 {
+        self.chain
+            .ledger()
+            .transparent()
+            .balance(&self.alice.transparent_address(), &self.btc)
+    }
+}
+
+#[test]
+fn shields_and_unshields_between_notes_and_accounts() {
+    let mut setup = setup();
+    assert_eq!(setup.transparent_balance(), 3 * COIN);
+    assert_eq!(setup.alice.balance(&setup.btc).unwrap().spendable, 2 * COIN);
+
+    let shield = setup.shield(COIN);
+    setup.submit(shield).unwrap();
+    assert_eq!(setup.transparent_balance(), 2 * COIN);
+    assert_eq!(setup.alice.balance(&setup.btc).unwrap().spendable, 3 * COIN);
+    let address = setup.alice.transparent_address();
+    assert_eq!(setup.chain.ledger().transparent().nonce(&address), 1);
+}
+
+#[test]
+fn transfers_between_accounts_without_notes() {
+    let mut setup = setup();
+    let nonce = setup
+        .chain
+        .ledger()
+        .transparent()
+        .nonce(&setup.alice.transparent_address());
+    // 透明なアカウントどうしの送金はノートを使わないので、バインディング署名の検証鍵が単位元になる
+    let transfer = TransactionBuilder::new(&setup.alice)
+        .add_transparent_input(setup.btc, COIN, nonce)
+        .add_transparent_output(PRODUCER, setup.btc, COIN)
+        .build(setup.chain.ledger().proof_system())
+        .unwrap();
+    assert!(transfer.inputs.is_empty() && transfer.outputs.is_empty());
+    setup.submit(transfer).unwrap();
+    assert_eq!(setup.transparent_balance(), 2 * COIN);
+    let ledger = setup.chain.ledger();
+    assert_eq!(ledger.transparent().balance(&PRODUCER, &setup.btc), COIN);
+}
+
+#[test]
+fn rejects_replayed_shielding_and_unshielding() {
+    let mut setup = setup();
+    // 透明な入力はノンスが進んでいるので再び使えない
+    let shield = setup.shield(COIN);
+    setup.submit(shield.clone()).unwrap();
+    let address = setup.alice.transparent_address();
+    setup.assert_rejected(shield, ValidationError::StaleNonce(address));
+
+    // 引き出しの入力はヌリファイアが公開済みなので再び使えない
+    let unshield = setup.unshield(COIN).unwrap();
+    setup.submit(unshield.clone()).unwrap();
+    let nullifier = unshield.inputs[0].nullifier;
+    setup.assert_rejected(unshield, ValidationError::DoubleSpend(nullifier));
+}
+
+#[test]
+fn rejects_inputs_signed_with_the_wrong_key() {
+    let mut setup = setup();
+    let address = setup.alice.transparent_address();
+    let other = TransparentKey::random();
+
+    // Aliceのアカウントからの入力に、別の鍵で署名する
+    let mut shield = setup.shield(COIN);
+    shield.transparent_inputs[0].signature = other.sign(&shield.sighash());
+    setup.assert_rejected(shield, ValidationError::BadTransparentSignature(address));
+
+    // 公開鍵ごと差し替えても、アドレスに対応しない鍵では引き出せない
+    let mut shield = setup.shield(COIN);
+    shield.transparent_inputs[0].public_key = other.public_key();
+    shield.transparent_inputs[0].signature = other.sign(&shield.sighash());
+    setup.assert_rejected(shield, ValidationError::BadTransparentSignature(address));
+
+    // 引き出しの入力も、ノートの鍵以外による署名では消費できない
+    let mut unshield = setup.unshield(COIN).unwrap();
+    let nullifier = unshield.inputs[0].nullifier;
+    unshield.inputs[0].spend_auth_sig =
+        Signature::sign(spend_auth_base(), &Scalar::random(), &unshield.sighash());
+    setup.assert_rejected(unshield, ValidationError::BadSignature(nullifier));
+}
+
+#[test]
+fn rejects_overdrafts() {
+    let mut setup = setup();
+    let address = setup.alice.transparent_address();
+    // 残高より多く透明なアカウントから引き出すと、台帳が拒否する
+    let shield = setup.shield(4 * COIN);
+    setup.assert_rejected(
+        shield,
+        ValidationError::InsufficientTransparentBalance(address, setup.btc),
+    );
+
+    // ノートより多く透明なアカウントへ引き出すトランザクションは作れない
+    assert!(matches!(
+        setup.unshield(3 * COIN),
+        Err(BuildError::InsufficientFunds {
+            available,
+            ..
+        }) if available == 2 * COIN
+    ));
+    assert_eq!(setup.transparent_balance(), 3 * COIN);
+    assert_eq!(setup.alice.balance(&setup.btc).unwrap().spendable, 2 * COIN);
+}
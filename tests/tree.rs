@@ -0,0 +1,103 @@
+mod common;
+
+use masp_simulation::commitment::NoteCommitment;
+use masp_simulation::ledger::Ledger;
+use masp_simulation::tree::{CommitmentTree, TREE_DEPTH};
+
+use common::Setup;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn only_checkpointed_roots_are_anchors() {
+    let mut tree = CommitmentTree::new();
+    assert!(tree.is_known_anchor(&tree.root()));
+
+    tree.append(NoteCommitment([1; 32]));
+    let partial = tree.root();
+    tree.append(NoteCommitment([2; 32]));
+    assert!(!tree.is_known_anchor(&partial));
+    assert!(!tree.is_known_anchor(&tree.root()));
+
+    let root = tree.checkpoint();
+    assert_eq!(root, tree.root());
+    assert!(tree.is_known_anchor(&root));
+    assert!(!tree.is_known_anchor(&partial));
+}
+
+#[test]
+fn ledger_records_one_anchor_per_transaction() {
+    let setup = Setup::new();
+    let mut ledger = setup.chain.ledger().clone();
+    let issuance = setup.mint(&[
+        (setup.alice.address(), setup.btc, 1),
+        (setup.bob.address(), setup.btc, 2),
+    ]);
+    assert_eq!(issuance.outputs.len(), 2);
+
+    // 1つ目の出力だけを追記した途中のルート
+    let mut partial = ledger.tree().clone();
+    partial.append(issuance.outputs[0].encrypted.cm);
+    let partial = partial.root();
+
+    ledger.apply(&issuance).unwrap();
+    assert!(ledger.tree().is_known_anchor(&ledger.tree().root()));
+    assert!(!ledger.tree().is_known_anchor(&partial));
+
+    // 復元した台帳は最終状態のルートを受け付ける
+    let restored = Ledger::restore(ledger.snapshot(), ledger.proof_system().clone());
+    assert!(restored.tree().is_known_anchor(&ledger.tree().root()));
+    assert!(!restored.tree().is_known_anchor(&partial));
+}
+
+#[test]
+fn roots_match_known_answers() {
+    // 空のツリー、葉が1つのツリー、葉が3つのツリーのルート
+    let mut tree = CommitmentTree::new();
+    assert_eq!(
+        hex(&tree.root().0),
+        "50c61410f4538d498a7c99ea90ef5cba76990f12667f37c3acf6aec5ddc0cf87"
+    );
+    tree.append(NoteCommitment([1; 32]));
+    assert_eq!(
+        hex(&tree.root().0),
+        "e5b4ce0376bde730f141b36d4c6b6d6e750863a8e84273644288d8df5ede105b"
+    );
+    tree.append(NoteCommitment([2; 32]));
+    tree.append(NoteCommitment([3; 32]));
+    assert_eq!(
+        hex(&tree.root().0),
+        "119d8b74dd8e7090ae5cad0bd491f319d7d66ab105b6160e32344d3b4eec08b2"
+    );
+}
+
+#[test]
+fn paths_lead_to_the_root() {
+    let mut tree = CommitmentTree::new();
+    let leaves: Vec<NoteCommitment> = (1..=5).map(|byte| NoteCommitment([byte; 32])).collect();
+    for (index, cm) in leaves.iter().enumerate() {
+        assert_eq!(tree.append(*cm), index as u64);
+    }
+    let root = tree.root();
+    for (position, cm) in leaves.iter().enumerate() {
+        let path = tree.witness(position as u64).unwrap();
+        assert_eq!(path.position, position as u64);
+        assert_eq!(path.auth_path.len(), TREE_DEPTH);
+        assert_eq!(path.root(cm), root);
+        // 別のコミットメントや別の位置では同じルートにならない
+        assert_ne!(path.root(&NoteCommitment([9; 32])), root);
+        let mut moved = path.clone();
+        moved.position ^= 1;
+        assert_ne!(moved.root(cm), root);
+    }
+    assert!(tree.witness(leaves.len() as u64).is_none());
+
+    // 葉を追記すると、古いパスは古いルートにしかつながらない
+    let stale = tree.witness(0).unwrap();
+    tree.append(NoteCommitment([6; 32]));
+    assert_eq!(stale.root(&leaves[0]), root);
+    assert_ne!(tree.root(), root);
+    assert_eq!(tree.witness(0).unwrap().root(&leaves[0]), tree.root());
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use masp_simulation::asset::AssetType;
+use masp_simulation::error::ValidationError;
+use masp_simulation::transaction::{Transaction, UnauthorizedTransaction};
+use masp_simulation::transparent::TransparentAddress;
+use masp_simulation::tree::Anchor;
+
+use common::{Setup, COIN, PRODUCER};
+
+// Aliceが5 BTCを持つチェーン
+fn setup() -> Setup {
+    let mut setup = Setup::new();
+    let issuance = setup.mint(&[(setup.alice.address(), setup.btc, 5 * COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup.sync();
+    setup
+}
+
+impl Setup {
+    // 入力も出力もないトランザクション。transaction で中身を足してから証明を作る
+    fn empty(
+        &self,
+        transaction: impl FnOnce(UnauthorizedTransaction) -> UnauthorizedTransaction,
+    ) -> Transaction {
+        transaction(UnauthorizedTransaction::new(
+            self.alice.anchor(),
+            Vec::new(),
+            Vec::new(),
+        ))
+        .prove(self.chain.ledger().proof_system())
+        .unwrap()
+    }
+
+    // 発行者が透明なアカウント address へ BTC を amount だけミントするトランザクション
+    fn mint_transparent(&self, address: TransparentAddress, amount: u64) -> Transaction {
+        let mut transaction =
+            UnauthorizedTransaction::new(self.alice.anchor(), Vec::new(), Vec::new())
+                .add_issuance(self.issuer_key.public_key(), self.btc, amount)
+                .add_transparent_output(address, self.btc, amount);
+        transaction.sign_issuance(&self.issuer_key);
+        transaction
+            .prove(self.chain.ledger().proof_system())
+            .unwrap()
+    }
+}
+
+#[test]
+fn rejects_unknown_asset_types() {
+    let setup = setup();
+    let unknown = AssetType::new("DOGE", 8, None);
+    let transaction = setup.empty(|transaction| transaction.with_value_balance(unknown, 0));
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::UnknownAssetType(unknown))
+    );
+    let transaction = setup.empty(|transaction| {
+        transaction.add_transparent_output(setup.bob.transparent_address(), unknown, COIN)
+    });
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::UnknownAssetType(unknown))
+    );
+}
+
+#[test]
+fn rejects_unknown_anchors() {
+    let setup = setup();
+    let transaction = UnauthorizedTransaction::new(Anchor([1; 32]), Vec::new(), Vec::new())
+        .prove(setup.chain.ledger().proof_system())
+        .unwrap();
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::BadAnchor(Anchor([1; 32])))
+    );
+}
+
+#[test]
+fn rejects_unbalanced_values() {
+    let setup = setup();
+    // シールドされたプールから1 BTCが出ていくと主張するが、行き先がない
+    let transaction =
+        setup.empty(|transaction| transaction.with_value_balance(setup.btc, COIN as i64));
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::ValueImbalance(setup.btc))
+    );
+}
+
+#[test]
+fn rejects_value_balances_without_shielded_actions() {
+    let setup = setup();
+    // 透明な出力とは釣り合っているが、入力も出力もないのでプールから出ていく1 BTCの出どころがない
+    let transaction = setup.empty(|transaction| {
+        transaction
+            .with_value_balance(setup.btc, COIN as i64)
+            .add_transparent_output(setup.bob.transparent_address(), setup.btc, COIN)
+    });
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::BadBindingSignature)
+    );
+}
+
+#[test]
+fn rejects_supply_overflows() {
+    let mut setup = setup();
+    let address = setup.bob.transparent_address();
+    // 総供給量の5 BTCに u64::MAX を足すことはできない
+    let transaction = setup.mint_transparent(address, u64::MAX);
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::SupplyOverflow(setup.btc))
+    );
+
+    // 残高のあるアカウントへの入金が表現できなければ、総供給量より先に残高で拒否する
+    let deposit = setup.mint_transparent(address, COIN);
+    setup.chain.produce_block(vec![deposit], PRODUCER).unwrap();
+    setup.sync();
+    let transaction = setup.mint_transparent(address, u64::MAX);
+    assert_eq!(
+        setup.chain.ledger().validate(&transaction),
+        Err(ValidationError::TransparentBalanceOverflow(
+            address, setup.btc
+        ))
+    );
+}
@@ -0,0 +1,300 @@
+mod common;
+
+use masp_simulation::block::{transactions_digest, Block, BlockHash, BlockHeader};
+use masp_simulation::builder::TransactionBuilder;
+use masp_simulation::error::{BlockError, BuildError};
+use masp_simulation::note::Note;
+use masp_simulation::transaction::UnauthorizedTransaction;
+use masp_simulation::tree::CommitmentTree;
+use masp_simulation::wallet::{Balance, NoteFilter, NoteStatus};
+
+use common::{Setup, COIN, PRODUCER};
+
+// 発行者がAliceへ5 BTCをミントしたブロックを1つ含むチェーン。ウォレットはまだ同期していない
+fn setup() -> Setup {
+    let mut setup = Setup::new();
+    let issuance = setup.mint(&[(setup.alice.address(), setup.btc, 5 * COIN)]);
+    setup.chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    setup
+}
+
+#[test]
+fn sync_finds_notes_and_tracks_spends() {
+    let Setup {
+        mut chain,
+        btc,
+        mut alice,
+        mut bob,
+        ..
+    } = setup();
+    alice.sync(&chain.blocks()[0]).unwrap();
+    bob.sync(&chain.blocks()[0]).unwrap();
+    assert_eq!(alice.height(), 1);
+    assert_eq!(alice.anchor(), chain.ledger().tree().root());
+    assert_eq!(alice.notes().len(), 1);
+    assert!(bob.notes().is_empty());
+
+    let payment = TransactionBuilder::new(&alice)
+        .add_output(bob.address(), btc, 2 * COIN)
+        .build(chain.ledger().proof_system())
+        .unwrap();
+    let block = chain.produce_block(vec![payment], PRODUCER).unwrap();
+    alice.sync(block).unwrap();
+    bob.sync(block).unwrap();
+
+    // 消費したノートは消費済みとして残り、お釣りのノートが加わる
+    let notes = alice.notes();
+    assert_eq!(notes.len(), 2);
+    assert!(notes[0].spent);
+    assert!(!notes[1].spent);
+    assert_eq!(notes[1].note.amount, 3 * COIN);
+    assert_eq!(bob.unspent_notes().count(), 1);
+
+    // 未消費のノートの認証パスは、同期したツリーのルートに対するものに更新されている
+    let root = chain.ledger().tree().root();
+    for received in alice.unspent_notes().chain(bob.unspent_notes()) {
+        assert_eq!(received.witness.root(&received.note.commit()), root);
+    }
+}
+
+#[test]
+fn sync_rejects_blocks_that_do_not_extend_the_wallet() {
+    let Setup {
+        mut chain,
+        mut alice,
+        ..
+    } = setup();
+    chain.produce_block(Vec::new(), PRODUCER).unwrap();
+
+    // ブロックを飛ばして同期することはできない
+    assert_eq!(
+        alice.sync(&chain.blocks()[1]),
+        Err(BlockError::WrongHeight {
+            expected: 1,
+            found: 2
+        })
+    );
+
+    let mut orphan = chain.blocks()[0].clone();
+    orphan.header.prev_hash = BlockHash([1; 32]);
+    assert_eq!(
+        alice.sync(&orphan),
+        Err(BlockError::UnknownParent(BlockHash([1; 32])))
+    );
+
+    // 出力とヘッダのルートが一致しないブロックでは、ノートを受け取らない
+    let mut forged = chain.blocks()[0].clone();
+    forged.header.tree_root = chain.ledger().tree().root();
+    forged.transactions.clear();
+    assert_eq!(
+        alice.sync(&forged),
+        Err(BlockError::TreeRootMismatch(forged.header.tree_root))
+    );
+    assert_eq!(alice.height(), 0);
+    assert!(alice.notes().is_empty());
+
+    for block in chain.blocks() {
+        alice.sync(block).unwrap();
+    }
+    assert_eq!(alice.height(), 2);
+    assert_eq!(alice.unspent_notes().count(), 1);
+}
+
+#[test]
+fn change_is_pending_until_the_transaction_is_confirmed() {
+    let Setup {
+        mut chain,
+        btc,
+        mut alice,
+        mut bob,
+        ..
+    } = setup();
+    alice.sync(&chain.blocks()[0]).unwrap();
+    bob.sync(&chain.blocks()[0]).unwrap();
+    assert_eq!(
+        alice.balance(&btc).unwrap(),
+        Balance {
+            spendable: 5 * COIN,
+            pending: 0
+        }
+    );
+
+    let payment = TransactionBuilder::new(&alice)
+        .add_output(bob.address(), btc, 2 * COIN)
+        .build(chain.ledger().proof_system())
+        .unwrap();
+    alice.mark_pending(&payment);
+    assert_eq!(
+        alice.balance(&btc).unwrap(),
+        Balance {
+            spendable: 0,
+            pending: 3 * COIN
+        }
+    );
+    assert_eq!(alice.balance(&btc).unwrap().total().unwrap(), 3 * COIN);
+    assert_eq!(
+        alice.note_status(&alice.notes()[0]),
+        NoteStatus::PendingSpend
+    );
+    // 送信中のトランザクションで消費するノートは、次のトランザクションに使わない
+    assert!(matches!(
+        TransactionBuilder::new(&alice)
+            .add_output(bob.address(), btc, COIN)
+            .build(chain.ledger().proof_system()),
+        Err(BuildError::InsufficientFunds { available: 0, .. })
+    ));
+    // 受取人は、ブロックに含まれるまで何も受け取らない
+    assert_eq!(bob.balance(&btc).unwrap(), Balance::default());
+
+    let block = chain.produce_block(vec![payment], PRODUCER).unwrap();
+    alice.sync(block).unwrap();
+    bob.sync(block).unwrap();
+    assert_eq!(
+        alice.balance(&btc).unwrap(),
+        Balance {
+            spendable: 3 * COIN,
+            pending: 0
+        }
+    );
+    assert_eq!(alice.pending_change().count(), 0);
+    assert_eq!(alice.note_status(&alice.notes()[0]), NoteStatus::Spent);
+    assert_eq!(bob.balance(&btc).unwrap().spendable, 2 * COIN);
+}
+
+#[test]
+fn cancelled_transactions_release_their_notes() {
+    let Setup {
+        chain,
+        btc,
+        mut alice,
+        bob,
+        ..
+    } = setup();
+    alice.sync(&chain.blocks()[0]).unwrap();
+    let payment = TransactionBuilder::new(&alice)
+        .add_output(bob.address(), btc, 2 * COIN)
+        .build(chain.ledger().proof_system())
+        .unwrap();
+    let txid = alice.mark_pending(&payment);
+    assert_eq!(alice.spendable_notes().count(), 0);
+
+    assert!(alice.cancel_pending(&txid));
+    assert!(!alice.cancel_pending(&txid));
+    assert_eq!(alice.balance(&btc).unwrap().spendable, 5 * COIN);
+    assert_eq!(alice.balance(&btc).unwrap().pending, 0);
+}
+
+#[test]
+fn lists_notes_by_asset_status_and_amount() {
+    let setup = Setup::new();
+    let (btc, eth) = (setup.btc, setup.eth);
+    let issuance = setup.mint(&[
+        (setup.alice.address(), btc, COIN),
+        (setup.alice.address(), btc, 2 * COIN),
+        (setup.alice.address(), eth, 4 * COIN),
+    ]);
+    let Setup {
+        mut chain,
+        mut alice,
+        ..
+    } = setup;
+    let block = chain.produce_block(vec![issuance], PRODUCER).unwrap();
+    alice.sync(block).unwrap();
+
+    // 2 BTCのノートを自分宛てに送り直し、消費済みのノートを作る
+    let transfer = TransactionBuilder::new(&alice)
+        .add_output(alice.address(), btc, 2 * COIN)
+        .build(chain.ledger().proof_system())
+        .unwrap();
+    let block = chain.produce_block(vec![transfer], PRODUCER).unwrap();
+    alice.sync(block).unwrap();
+
+    let amounts = |filter: NoteFilter| -> Vec<u64> {
+        alice
+            .list_notes(&filter)
+            .iter()
+            .map(|received| received.note.amount)
+            .collect()
+    };
+    assert_eq!(
+        amounts(NoteFilter::new()),
+        vec![COIN, 2 * COIN, 4 * COIN, 2 * COIN]
+    );
+    assert_eq!(
+        amounts(NoteFilter::new().with_asset_type(btc)),
+        vec![COIN, 2 * COIN, 2 * COIN]
+    );
+    assert_eq!(
+        amounts(
+            NoteFilter::new()
+                .with_asset_type(btc)
+                .with_status(NoteStatus::Spendable)
+        ),
+        vec![COIN, 2 * COIN]
+    );
+    assert_eq!(
+        amounts(NoteFilter::new().with_status(NoteStatus::Spent)),
+        vec![2 * COIN]
+    );
+    assert_eq!(
+        amounts(NoteFilter::new().with_min_amount(2 * COIN)),
+        vec![2 * COIN, 4 * COIN, 2 * COIN]
+    );
+
+    let balances = alice.balances().unwrap();
+    assert_eq!(balances.len(), 2);
+    assert_eq!(balances[&btc].total().unwrap(), 3 * COIN);
+    assert_eq!(balances[&eth].total().unwrap(), 4 * COIN);
+}
+
+#[test]
+fn sums_that_overflow_are_rejected() {
+    let Setup {
+        chain,
+        btc,
+        mut alice,
+        ..
+    } = Setup::new();
+    assert_eq!(
+        Balance {
+            spendable: u64::MAX,
+            pending: 1
+        }
+        .total(),
+        None
+    );
+
+    // 台帳の検証を経ずに、合計が u64 を超える2つのノートを Alice へ送るブロックを組み立てる
+    let prover = chain.ledger().proof_system();
+    let outputs = vec![
+        Note::new(btc, u64::MAX, alice.address()),
+        Note::new(btc, 1, alice.address()),
+    ];
+    let transaction = UnauthorizedTransaction::new(alice.anchor(), Vec::new(), outputs)
+        .prove(prover)
+        .unwrap();
+    let mut tree = CommitmentTree::new();
+    for output in &transaction.outputs {
+        tree.append(output.encrypted.cm);
+    }
+    let transactions = vec![transaction];
+    let block = Block {
+        header: BlockHeader {
+            prev_hash: BlockHash::GENESIS_PARENT,
+            height: 1,
+            tree_root: tree.root(),
+            nullifier_digest: [0; 32],
+            transactions_digest: transactions_digest(&transactions),
+            fee_recipient: PRODUCER,
+        },
+        transactions,
+    };
+    alice.sync(&block).unwrap();
+
+    assert_eq!(alice.balances(), Err(BuildError::AmountOverflow(btc)));
+    assert_eq!(alice.balance(&btc), Err(BuildError::AmountOverflow(btc)));
+    assert_eq!(
+        alice.merge_notes(btc, None, prover).err(),
+        Some(BuildError::AmountOverflow(btc))
+    );
+}